@@ -0,0 +1,196 @@
+//! Machine-readable reports of problems for other tools, selected with
+//! `felix check --format`. The JSON format is described by the schema in
+//! `problems.schema.json` next to the crate's manifest. The SARIF format
+//! follows version 2.1.0 of the standard.
+use std::path::{Component, Path};
+
+use clap::ValueEnum;
+use felix_common::{explain, Code, Problem, Severity, SrcLoc};
+use serde_json::{json, Value};
+
+/// The version of the JSON format. It is increased whenever a change to
+/// the format is not backwards compatible.
+const JSON_VERSION: u32 = 1;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum Format {
+    /// Problems with source snippets for humans.
+    #[default]
+    Text,
+    /// All problems in one JSON document.
+    Json,
+    /// A SARIF 2.1.0 log, as understood by code review tools.
+    Sarif,
+}
+
+pub fn json(name: &str, problems: &[Problem]) -> Value {
+    json!({
+        "version": JSON_VERSION,
+        "file": name,
+        "problems": problems,
+    })
+}
+
+/// A SARIF log of the problems of the given file, or of stdin for `None`.
+pub fn sarif(file: Option<&Path>, problems: &[Problem]) -> Value {
+    let artifact = artifact_location(file);
+    let rules: Vec<_> = Code::ALL
+        .into_iter()
+        .map(|code| {
+            json!({
+                "id": code.as_str(),
+                "shortDescription": {"text": explain::title(code)},
+                "help": {
+                    "text": format!("Run `felix explain {}` for details.", code),
+                    "markdown": explain::explain(code),
+                },
+            })
+        })
+        .collect();
+    let results: Vec<_> = problems
+        .iter()
+        .map(|problem| sarif_result(&artifact, problem))
+        .collect();
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "felix",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_HOMEPAGE"),
+                    "rules": rules,
+                },
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }],
+    })
+}
+
+fn sarif_result(artifact: &Value, problem: &Problem) -> Value {
+    let level = match problem.severity {
+        Severity::ERROR => "error",
+        Severity::WARNING => "warning",
+        Severity::INFO | Severity::HINT => "note",
+    };
+    // NOTE: SARIF has no notes. We append them to the message like the
+    // language server does.
+    let message = std::iter::once(problem.message.clone())
+        .chain(problem.notes.iter().map(|note| format!("note: {}", note)))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let related_locations: Vec<_> = problem
+        .labels
+        .iter()
+        .enumerate()
+        .map(|(id, label)| {
+            json!({
+                "id": id,
+                "physicalLocation": location(artifact, label.start, label.end),
+                "message": {"text": label.message},
+            })
+        })
+        .collect();
+    let fixes: Vec<_> = problem
+        .suggestions
+        .iter()
+        .map(|suggestion| {
+            let replacements: Vec<_> = suggestion
+                .edits
+                .iter()
+                .map(|edit| {
+                    json!({
+                        "deletedRegion": region(edit.start, edit.end),
+                        "insertedContent": {"text": edit.text},
+                    })
+                })
+                .collect();
+            json!({
+                "description": {"text": suggestion.message},
+                "artifactChanges": [{
+                    "artifactLocation": artifact,
+                    "replacements": replacements,
+                }],
+            })
+        })
+        .collect();
+    let rule_index = Code::ALL
+        .iter()
+        .position(|code| *code == problem.code)
+        .expect("all codes are rules");
+    json!({
+        "ruleId": problem.code.as_str(),
+        "ruleIndex": rule_index,
+        "level": level,
+        "message": {"text": message},
+        "locations": [{"physicalLocation": location(artifact, problem.start, problem.end)}],
+        "relatedLocations": related_locations,
+        "fixes": fixes,
+    })
+}
+
+fn location(artifact: &Value, start: SrcLoc, end: SrcLoc) -> Value {
+    json!({
+        "artifactLocation": artifact,
+        "region": region(start, end),
+    })
+}
+
+/// SARIF identifies files by URIs. Stdin has none, so it only gets a
+/// description.
+fn artifact_location(file: Option<&Path>) -> Value {
+    match file {
+        Some(file) => json!({"uri": uri(file)}),
+        None => json!({"description": {"text": "<stdin>"}}),
+    }
+}
+
+/// A `file` URI for absolute paths and a relative reference for relative
+/// ones, with all segments percent-encoded.
+fn uri(path: &Path) -> String {
+    let mut prefix = String::new();
+    let mut segments = Vec::new();
+    for component in path.components() {
+        match component {
+            // NOTE: Windows drives like `C:` become `/C:`.
+            Component::Prefix(drive) => {
+                prefix = format!("/{}", drive.as_os_str().to_string_lossy())
+            }
+            Component::RootDir => {}
+            Component::CurDir => segments.push(String::from(".")),
+            Component::ParentDir => segments.push(String::from("..")),
+            Component::Normal(segment) => segments.push(percent_encode(&segment.to_string_lossy())),
+        }
+    }
+    let segments = segments.join("/");
+    if path.has_root() {
+        format!("file://{}/{}", prefix, segments)
+    } else {
+        segments
+    }
+}
+
+/// Percent-encode everything but the unreserved characters of RFC 3986.
+fn percent_encode(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// SARIF counts lines and columns from one.
+fn region(start: SrcLoc, end: SrcLoc) -> Value {
+    json!({
+        "startLine": start.line + 1,
+        "startColumn": start.column + 1,
+        "endLine": end.line + 1,
+        "endColumn": end.column + 1,
+    })
+}
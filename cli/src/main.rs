@@ -0,0 +1,636 @@
+//! The `felix` command-line tool. It reads a program from a file or from
+//! stdin and runs one phase of the pipeline on it.
+//!
+//! The exit code is 0 on success, 1 if the program has problems and 2 if
+//! the tool is used incorrectly or cannot read its input.
+use std::{
+    fs,
+    io::{self, IsTerminal, Read, Write},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use clap::{Parser as _, Subcommand};
+
+use felix_common::{explain, render::Renderer, srcloc::Mapper, Code, Problem, Severity};
+use felix_parser::{
+    style,
+    syntax::{SyntaxKind, COMMENTS},
+    ParseResult, Parser,
+};
+use felix_type_checker::{ast, Checker, Context, Type};
+
+use crate::format::Format;
+
+mod format;
+mod repl;
+
+#[derive(clap::Parser)]
+#[command(name = "felix", version, about = "Tools for the felix language")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the concrete syntax tree of a program.
+    Parse(Input),
+    /// Print the type of a program.
+    Check(Check),
+    /// Evaluate a program and print its value.
+    Run(Input),
+    /// Print a program in canonical layout. Programs with comments are
+    /// refused since the layout does not preserve them.
+    Fmt(Input),
+    /// Rewrite a program to use either the ASCII or the Unicode spelling
+    /// of tokens like `->` and `→`. Comments are preserved.
+    Normalize(Normalize),
+    /// Start an interactive session.
+    Repl,
+    /// Explain a problem code, e.g. P0001.
+    Explain { code: Code },
+}
+
+#[derive(clap::Args)]
+struct Input {
+    /// The file to read the program from. Reads from stdin when omitted.
+    file: Option<PathBuf>,
+}
+
+#[derive(clap::Args)]
+struct Check {
+    #[command(flatten)]
+    input: Input,
+    /// How to report problems. The machine-readable formats are printed
+    /// to stdout instead of the type.
+    #[arg(long, value_enum, default_value_t)]
+    format: Format,
+}
+
+#[derive(clap::Args)]
+struct Normalize {
+    #[command(flatten)]
+    input: Input,
+    /// The spelling to use.
+    #[arg(long, value_enum)]
+    style: Style,
+    /// Overwrite the file instead of printing the result.
+    #[arg(long, short, requires = "file")]
+    write: bool,
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum Style {
+    /// `\`, `->`, `forall`, `/\`, `\/` and `~`.
+    Ascii,
+    /// `λ`, `→`, `∀`, `∧`, `∨` and `¬`.
+    Unicode,
+}
+
+impl From<Style> for style::Style {
+    fn from(style: Style) -> Self {
+        match style {
+            Style::Ascii => Self::Ascii,
+            Style::Unicode => Self::Unicode,
+        }
+    }
+}
+
+/// The ways running a command can fail.
+#[derive(Debug, Eq, PartialEq)]
+enum Failure {
+    /// The program has problems, which have been reported.
+    Program,
+    /// The input could not be read or the output could not be written.
+    Io,
+}
+
+impl From<io::Error> for Failure {
+    fn from(_: io::Error) -> Self {
+        Self::Io
+    }
+}
+
+impl From<Failure> for ExitCode {
+    fn from(failure: Failure) -> Self {
+        match failure {
+            Failure::Program => ExitCode::from(1),
+            Failure::Io => ExitCode::from(2),
+        }
+    }
+}
+
+type Result = std::result::Result<(), Failure>;
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let input = match &cli.command {
+        Command::Parse(input)
+        | Command::Check(Check { input, .. })
+        | Command::Run(input)
+        | Command::Fmt(input)
+        | Command::Normalize(Normalize { input, .. }) => input,
+        Command::Repl => {
+            return match repl::Repl::new().run(io::stdin().lock(), &mut io::stdout()) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(_) => Failure::Io.into(),
+            };
+        }
+        Command::Explain { code } => {
+            print!("{}", explain::explain(*code));
+            return ExitCode::SUCCESS;
+        }
+    };
+    let (name, text) = match read(input) {
+        Ok(source) => source,
+        Err(error) => {
+            let name = input.file.as_ref().map_or_else(
+                || String::from("<stdin>"),
+                |file| file.display().to_string(),
+            );
+            eprintln!("felix: cannot read {}: {}", name, error);
+            return Failure::Io.into();
+        }
+    };
+    // NOTE: Problems are reported on stderr and hence it decides about
+    // colours. See https://no-color.org for `NO_COLOR`.
+    let source = Source {
+        name: &name,
+        text: &text,
+        color: io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+    };
+    match execute(&cli.command, &source, &mut io::stdout(), &mut io::stderr()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(failure) => failure.into(),
+    }
+}
+
+fn read(input: &Input) -> io::Result<(String, String)> {
+    match &input.file {
+        Some(file) => Ok((file.display().to_string(), fs::read_to_string(file)?)),
+        None => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text)?;
+            Ok((String::from("<stdin>"), text))
+        }
+    }
+}
+
+struct Source<'a> {
+    /// The name of the input used in messages.
+    name: &'a str,
+    text: &'a str,
+    /// Whether to colour reported problems.
+    color: bool,
+}
+
+impl Source<'_> {
+    fn report(&self, mapper: &Mapper, problems: &[Problem], err: &mut impl Write) -> Result {
+        let renderer = Renderer::new(self.name, mapper).with_color(self.color);
+        for problem in problems {
+            writeln!(err, "{}", renderer.render(problem))?;
+        }
+        Ok(())
+    }
+
+    /// Parse the program and report all problems. Fails if there are any.
+    fn parse(&self, err: &mut impl Write) -> std::result::Result<ParseResult, Failure> {
+        let mapper = Mapper::new(self.text);
+        let result = Parser::new(self.text, &mapper).run(Parser::program);
+        if result.problems.is_empty() {
+            Ok(result)
+        } else {
+            self.report(&mapper, &result.problems, err)?;
+            Err(Failure::Program)
+        }
+    }
+
+    fn lower(&self, err: &mut impl Write) -> std::result::Result<ast::Expr, Failure> {
+        let result = self.parse(err)?;
+        Ok(felix_type_checker::lower::lower(&result.syntax))
+    }
+
+    /// Parse and type check the program. Returns its type or all problems
+    /// found.
+    fn check(&self) -> std::result::Result<Type, (Mapper, Vec<Problem>)> {
+        let mapper = Mapper::new(self.text);
+        let result = Parser::new(self.text, &mapper).run(Parser::program);
+        if !result.problems.is_empty() {
+            return Err((mapper, result.problems));
+        }
+        let expr = felix_type_checker::lower::lower(&result.syntax);
+        felix_type_checker::stlc::get()
+            .infer(&Context::new(), &expr)
+            .map_err(|error| {
+                let problem = error.to_problem(&mapper, &expr);
+                (mapper, vec![problem])
+            })
+    }
+
+    fn fail(&self, message: impl std::fmt::Display, err: &mut impl Write) -> Result {
+        writeln!(err, "{}: error: {}", self.name, message)?;
+        Err(Failure::Program)
+    }
+}
+
+fn execute(
+    command: &Command,
+    source: &Source,
+    out: &mut impl Write,
+    err: &mut impl Write,
+) -> Result {
+    match command {
+        Command::Parse(_) => {
+            // NOTE: The tree is printed even if the program has problems
+            // since it shows how the parser recovered from them.
+            let mapper = Mapper::new(source.text);
+            let result = Parser::new(source.text, &mapper).run(Parser::program);
+            write!(out, "{:#?}", result.syntax)?;
+            if result.problems.is_empty() {
+                Ok(())
+            } else {
+                source.report(&mapper, &result.problems, err)?;
+                Err(Failure::Program)
+            }
+        }
+        Command::Check(Check { input, format }) => {
+            let (r#type, problems) = match source.check() {
+                Ok(r#type) => (Some(r#type), Vec::new()),
+                Err((mapper, problems)) => {
+                    if *format == Format::Text {
+                        source.report(&mapper, &problems, err)?;
+                    }
+                    (None, problems)
+                }
+            };
+            let report = match format {
+                Format::Text => None,
+                Format::Json => Some(format::json(source.name, &problems)),
+                Format::Sarif => Some(format::sarif(input.file.as_deref(), &problems)),
+            };
+            match (report, r#type) {
+                (Some(report), _) => writeln!(out, "{:#}", report)?,
+                (None, Some(r#type)) => writeln!(out, "{}", r#type)?,
+                (None, None) => {}
+            }
+            if problems
+                .iter()
+                .any(|problem| problem.severity == Severity::ERROR)
+            {
+                Err(Failure::Program)
+            } else {
+                Ok(())
+            }
+        }
+        Command::Run(_) => {
+            let expr = source.lower(err)?;
+            let expr = match felix_core::erase(&expr) {
+                Ok(expr) => expr,
+                Err(error) => return source.fail(error, err),
+            };
+            match felix_interpreter::eval(&expr) {
+                Ok(value) => Ok(writeln!(out, "{}", value)?),
+                Err(error) => source.fail(error, err),
+            }
+        }
+        Command::Fmt(_) => {
+            let result = source.parse(err)?;
+            let has_comments = result
+                .syntax
+                .descendants_with_tokens()
+                .any(|element| match element.kind() {
+                    SyntaxKind::Token(token) => token.is(COMMENTS),
+                    SyntaxKind::Node(_) => false,
+                });
+            if has_comments {
+                return source.fail(
+                    "cannot format a program with comments since they would be lost",
+                    err,
+                );
+            }
+            let expr = felix_type_checker::lower::lower(&result.syntax);
+            Ok(writeln!(out, "{}", expr)?)
+        }
+        Command::Normalize(Normalize {
+            input,
+            style,
+            write,
+        }) => {
+            let text = style::normalize(source.text, (*style).into());
+            match &input.file {
+                Some(file) if *write => Ok(fs::write(file, text)?),
+                _ => Ok(write!(out, "{}", text)?),
+            }
+        }
+        Command::Repl | Command::Explain { .. } => {
+            unreachable!("the command does not read a source")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use clap::Parser as _;
+    use felix_common::{explain, Code};
+
+    use super::{execute, Check, Cli, Command, Failure, Format, Input, Normalize, Source, Style};
+
+    /// Run a command on the given program and return its outcome together
+    /// with everything it wrote to stdout and stderr.
+    fn run(
+        command: impl Fn(Input) -> Command,
+        text: &str,
+    ) -> (Result<(), Failure>, String, String) {
+        let source = Source {
+            name: "test.fx",
+            text,
+            color: false,
+        };
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+        let result = execute(&command(Input { file: None }), &source, &mut out, &mut err);
+        let out = String::from_utf8(out).unwrap();
+        let err = String::from_utf8(err).unwrap();
+        (result, out, err)
+    }
+
+    fn check(format: Format) -> impl Fn(Input) -> Command {
+        move |input| Command::Check(Check { input, format })
+    }
+
+    #[test]
+    fn cli_parse() {
+        let (result, out, err) = run(Command::Parse, "f unit");
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            out,
+            r#"PROGRAM@0..6
+  EXPR_APP@0..6
+    EXPR_VAR@0..1
+      ID_EXPR@0..1 "f"
+    WHITESPACE@1..2 " "
+    EXPR_UNIT@2..6
+      KW_UNIT@2..6 "unit"
+"#
+        );
+        assert_eq!(err, "");
+    }
+
+    #[test]
+    fn cli_parse_problems() {
+        let (result, out, err) = run(Command::Parse, "let x =\n  in x");
+        assert_eq!(result, Err(Failure::Program));
+        assert!(out.starts_with("PROGRAM@"));
+        assert_eq!(
+            err,
+            "\
+error[P0001]: Expected an expression, found `in`.
+ --> test.fx:2:3
+  |
+2 |   in x
+  |   ^^
+
+"
+        );
+    }
+
+    #[test]
+    fn cli_check() {
+        let (result, out, _) = run(check(Format::Text), "let f = λx:Unit. x in f unit");
+        assert_eq!(result, Ok(()));
+        assert_eq!(out, "Unit\n");
+    }
+
+    #[test]
+    fn cli_check_type_error() {
+        let (result, out, err) = run(check(Format::Text), "unit unit");
+        assert_eq!(result, Err(Failure::Program));
+        assert_eq!(out, "");
+        assert_eq!(
+            err,
+            "\
+error[T0003]: expected a function type, found Unit
+ --> test.fx:1:1
+  |
+1 | unit unit
+  | ^^^^^^^^^
+
+"
+        );
+    }
+
+    #[test]
+    fn cli_check_nested_type_error() {
+        let text = "let x = unit\nin x x";
+        let (result, _, err) = run(check(Format::Text), text);
+        assert_eq!(result, Err(Failure::Program));
+        assert_eq!(
+            err,
+            "\
+error[T0003]: expected a function type, found Unit
+ --> test.fx:2:4
+  |
+2 | in x x
+  |    ^^^
+
+"
+        );
+        let (_, out, _) = run(check(Format::Json), text);
+        let report: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(
+            report["problems"][0]["span"],
+            serde_json::json!({"start": 16, "end": 19})
+        );
+    }
+
+    #[test]
+    fn cli_check_json() {
+        let (result, out, err) = run(check(Format::Json), "# λ\nλx:Unit. )");
+        assert_eq!(result, Err(Failure::Program));
+        assert_eq!(err, "");
+        let report: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(report["version"], 1);
+        assert_eq!(report["file"], "test.fx");
+        let problem = &report["problems"][0];
+        assert_eq!(problem["code"], "P0001");
+        assert_eq!(problem["severity"], "ERROR");
+        assert_eq!(
+            problem["start"],
+            serde_json::json!({"line": 1, "column": 9})
+        );
+        assert_eq!(problem["span"], serde_json::json!({"start": 15, "end": 16}));
+
+        // NOTE: Keep the schema in sync with the serialisation of `Problem`.
+        let schema: serde_json::Value =
+            serde_json::from_str(include_str!("../problems.schema.json")).unwrap();
+        let keys = |value: &serde_json::Value| {
+            let mut keys: Vec<_> = value.as_object().unwrap().keys().cloned().collect();
+            keys.sort();
+            keys
+        };
+        assert_eq!(keys(&report), keys(&schema["properties"]));
+        assert_eq!(
+            keys(problem),
+            keys(&schema["$defs"]["problem"]["properties"])
+        );
+    }
+
+    #[test]
+    fn cli_check_json_success() {
+        let (result, out, _) = run(check(Format::Json), "unit");
+        assert_eq!(result, Ok(()));
+        let report: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(report["problems"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn cli_check_sarif() {
+        let (result, out, err) = run(check(Format::Sarif), "let f = λx.\n  x in f");
+        assert_eq!(result, Err(Failure::Program));
+        assert_eq!(err, "");
+        let log: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(log["version"], "2.1.0");
+        let run = &log["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "felix");
+        let result = &run["results"][0];
+        assert_eq!(result["ruleId"], "T0002");
+        assert_eq!(result["level"], "error");
+        let rule = &run["tool"]["driver"]["rules"][result["ruleIndex"].as_u64().unwrap() as usize];
+        assert_eq!(rule["id"], "T0002");
+        assert_eq!(
+            result["locations"][0]["physicalLocation"],
+            serde_json::json!({
+                "artifactLocation": {"description": {"text": "<stdin>"}},
+                "region": {"startLine": 1, "startColumn": 9, "endLine": 2, "endColumn": 4},
+            })
+        );
+    }
+
+    #[test]
+    fn cli_check_sarif_uri() {
+        for (file, uri) in [
+            ("test.fx", "test.fx"),
+            ("../my programs/1:1.fx", "../my%20programs/1%3A1.fx"),
+            ("/tmp/λ.fx", "file:///tmp/%CE%BB.fx"),
+        ] {
+            let command = |_| {
+                Command::Check(Check {
+                    input: Input {
+                        file: Some(PathBuf::from(file)),
+                    },
+                    format: Format::Sarif,
+                })
+            };
+            let (_, out, _) = run(command, "unit unit");
+            let log: serde_json::Value = serde_json::from_str(&out).unwrap();
+            let location = &log["runs"][0]["results"][0]["locations"][0]["physicalLocation"];
+            assert_eq!(
+                location["artifactLocation"],
+                serde_json::json!({"uri": uri})
+            );
+        }
+    }
+
+    #[test]
+    fn cli_run() {
+        let (result, out, _) = run(Command::Run, "let id = λx. x in id id");
+        assert_eq!(result, Ok(()));
+        assert_eq!(out, "<closure λx>\n");
+    }
+
+    #[test]
+    fn cli_run_error() {
+        let (result, _, err) = run(Command::Run, "unit unit");
+        assert_eq!(result, Err(Failure::Program));
+        assert_eq!(err, "test.fx: error: not a function: unit\n");
+    }
+
+    #[test]
+    fn cli_run_parse_problems() {
+        let (result, out, err) = run(Command::Run, "λx.");
+        assert_eq!(result, Err(Failure::Program));
+        assert_eq!(out, "");
+        assert!(err.contains(" --> test.fx:1:"));
+    }
+
+    #[test]
+    fn cli_fmt() {
+        let (result, out, _) = run(Command::Fmt, "let  f=(λx : Unit.x)in\n((f) unit)");
+        assert_eq!(result, Ok(()));
+        assert_eq!(out, "let f = λx:Unit. x in f unit\n");
+    }
+
+    #[test]
+    fn cli_fmt_comments() {
+        for text in [
+            "## The unit.\nlet x = unit in x",
+            "let x = unit in # Keep.\nx",
+            "let x = #| unit |# unit in x",
+        ] {
+            let (result, out, err) = run(Command::Fmt, text);
+            assert_eq!(result, Err(Failure::Program));
+            assert_eq!(out, "");
+            assert_eq!(
+                err,
+                "test.fx: error: cannot format a program with comments since they would be lost\n"
+            );
+        }
+    }
+
+    #[test]
+    fn cli_normalize() {
+        let normalize = |style| {
+            move |input| {
+                Command::Normalize(Normalize {
+                    input,
+                    style,
+                    write: false,
+                })
+            }
+        };
+        let text = "# λ, ->\nfun f:(Unit -> Unit) -> λx. f x\n";
+        let (result, out, _) = run(normalize(Style::Ascii), text);
+        assert_eq!(result, Ok(()));
+        assert_eq!(out, "# λ, ->\nfun f:(Unit -> Unit) -> \\x. f x\n");
+        let (result, out, _) = run(normalize(Style::Unicode), text);
+        assert_eq!(result, Ok(()));
+        assert_eq!(out, "# λ, ->\nfun f:(Unit → Unit) → λx. f x\n");
+        assert!(
+            Cli::try_parse_from(["felix", "normalize", "--style", "ascii", "--write"]).is_err()
+        );
+    }
+
+    #[test]
+    fn cli_explain() {
+        let cli = Cli::try_parse_from(["felix", "explain", "p0001"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Explain {
+                code: Code::UNEXPECTED_TOKEN
+            }
+        ));
+        assert!(Cli::try_parse_from(["felix", "explain", "P9999"]).is_err());
+    }
+
+    /// The fixed examples in the explanations pass all checks.
+    #[test]
+    fn cli_explanation_examples() {
+        for code in Code::ALL {
+            for example in explain::examples(code) {
+                // NOTE: Matches are checked by the match compiler's tests.
+                if example.language != explain::Language::Felix {
+                    continue;
+                }
+                let (result, _, err) = run(check(Format::Text), example.source);
+                if example.fails {
+                    assert!(err.contains(code.as_str()), "{}", err);
+                } else {
+                    assert_eq!(result, Ok(()), "{}", err);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,300 @@
+//! The interactive read-eval-print loop behind `felix repl`. Definitions of
+//! the form `let x = e` extend the context of the type checker and the
+//! environment of the interpreter for all later inputs.
+use std::io::{self, BufRead, Write};
+
+use felix_common::{srcloc::Mapper, Problem};
+use felix_interpreter::Env;
+use felix_parser::{ParseResult, Parser};
+use felix_type_checker::{
+    ast::{self, Binder},
+    lower, stlc, Checker, Context, Type, TypeSystem,
+};
+
+const PROMPT: &str = "> ";
+
+const HELP: &str = "\
+Enter an expression to evaluate it or `let x = e` to define `x`.
+Commands:
+  :type e            print the type of `e`
+  :ast e             print the abstract syntax tree of `e`
+  :cst e             print the concrete syntax tree of `e`
+  :system [stlc|hm]  print or change the type system
+  :help              print this help
+  :quit              leave the REPL";
+
+/// Whether the REPL should keep reading input.
+#[derive(Debug, Eq, PartialEq)]
+enum Flow {
+    Continue,
+    Quit,
+}
+
+pub struct Repl {
+    system: &'static TypeSystem,
+    ctx: Context,
+    env: Env,
+}
+
+impl Default for Repl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Repl {
+    pub fn new() -> Self {
+        Self {
+            system: stlc::get(),
+            ctx: Context::new(),
+            env: Env::new(),
+        }
+    }
+
+    /// Read lines from `input` and handle them until the input ends or the
+    /// user quits.
+    pub fn run(&mut self, mut input: impl BufRead, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "felix {} (:help for help)", env!("CARGO_PKG_VERSION"))?;
+        let mut line = String::new();
+        loop {
+            write!(out, "{}", PROMPT)?;
+            out.flush()?;
+            line.clear();
+            if input.read_line(&mut line)? == 0 {
+                return writeln!(out);
+            }
+            if self.handle(line.trim_end(), out)? == Flow::Quit {
+                return Ok(());
+            }
+        }
+    }
+
+    fn handle(&mut self, line: &str, out: &mut impl Write) -> io::Result<Flow> {
+        let Some(command) = line.strip_prefix(':') else {
+            if !line.trim().is_empty() {
+                self.define_or_eval(line, out)?;
+            }
+            return Ok(Flow::Continue);
+        };
+        let (name, arg) = command.split_once(' ').unwrap_or((command, ""));
+        // NOTE: The argument starts after the colon, the name and a space.
+        let column = (name.chars().count() + 2) as u32;
+        match name {
+            "type" => {
+                if let Some(syntax) = self.parse(arg, column, false, out)? {
+                    let expr = lower::lower(&syntax);
+                    match self.system.infer(&self.ctx, &expr) {
+                        Ok(r#type) => writeln!(out, "{} : {}", expr, r#type)?,
+                        Err(error) => writeln!(out, "error: {}", error)?,
+                    }
+                }
+            }
+            "ast" => {
+                if let Some(syntax) = self.parse(arg, column, false, out)? {
+                    writeln!(out, "{}", lower::lower(&syntax))?;
+                }
+            }
+            "cst" => {
+                // NOTE: The tree is printed even if the expression has
+                // problems since it shows how the parser recovered.
+                let mapper = Mapper::new(arg);
+                let result = Parser::new(arg, &mapper).run(Parser::program);
+                write!(out, "{:#?}", result.syntax)?;
+                report(&result.problems, column, out)?;
+            }
+            "system" => match arg.trim() {
+                "" => writeln!(out, "{}", self.system.name)?,
+                "stlc" => {
+                    self.system = stlc::get();
+                    writeln!(out, "{}", self.system.name)?;
+                }
+                "hm" => writeln!(out, "error: Hindley-Milner is not available yet")?,
+                system => writeln!(out, "error: unknown type system: {}", system)?,
+            },
+            "help" => writeln!(out, "{}", HELP)?,
+            "quit" => return Ok(Flow::Quit),
+            _ => writeln!(out, "error: unknown command :{} (:help for help)", name)?,
+        }
+        Ok(Flow::Continue)
+    }
+
+    /// Parse the input as a program, or as a definition too if requested,
+    /// and report its problems. The input starts at the given column of the
+    /// line.
+    fn parse(
+        &self,
+        input: &str,
+        column: u32,
+        definition: bool,
+        out: &mut impl Write,
+    ) -> io::Result<Option<felix_parser::syntax::Node>> {
+        let mapper = Mapper::new(input);
+        let parser = Parser::new(input, &mapper);
+        let ParseResult { syntax, problems } = if definition {
+            parser.run(Parser::repl_input)
+        } else {
+            parser.run(Parser::program)
+        };
+        if problems.is_empty() {
+            Ok(Some(syntax))
+        } else {
+            report(&problems, column, out)?;
+            Ok(None)
+        }
+    }
+
+    fn define_or_eval(&mut self, line: &str, out: &mut impl Write) -> io::Result<()> {
+        let Some(syntax) = self.parse(line, 0, true, out)? else {
+            return Ok(());
+        };
+        let result = match lower::lower_definition(&syntax) {
+            Some((binder, bindee)) => self.define(binder, &bindee),
+            None => self.eval(&lower::lower(&syntax)),
+        };
+        match result {
+            Ok(output) => writeln!(out, "{}", output),
+            Err(error) => writeln!(out, "error: {}", error),
+        }
+    }
+
+    fn define(&mut self, binder: Binder, bindee: &ast::Expr) -> Result<String, String> {
+        let r#type = self.infer(bindee)?;
+        if let Some(annot) = &binder.annot {
+            self.system
+                .equal(&r#type, annot)
+                .map_err(|error| error.to_string())?;
+        }
+        let value = self.value(bindee)?;
+        let output = format!("{} : {}", binder.name.as_str(), r#type);
+        self.ctx = self.ctx.extend(binder.name.clone(), r#type);
+        self.env = self.env.extend(binder.name.as_str().into(), value);
+        Ok(output)
+    }
+
+    fn eval(&self, expr: &ast::Expr) -> Result<String, String> {
+        let r#type = self.infer(expr)?;
+        let value = self.value(expr)?;
+        Ok(format!("{} : {}", value, r#type))
+    }
+
+    fn infer(&self, expr: &ast::Expr) -> Result<Type, String> {
+        self.system
+            .infer(&self.ctx, expr)
+            .map_err(|error| error.to_string())
+    }
+
+    fn value(&self, expr: &ast::Expr) -> Result<felix_interpreter::Value, String> {
+        let expr = felix_core::erase(expr).map_err(|error| error.to_string())?;
+        felix_interpreter::eval_in(&self.env, &expr).map_err(|error| error.to_string())
+    }
+}
+
+/// Report problems with one-based positions. Positions on the first line
+/// are shifted by the column the input starts at.
+fn report(problems: &[Problem], column: u32, out: &mut impl Write) -> io::Result<()> {
+    for problem in problems {
+        let start = problem.start;
+        let shift = if start.line == 0 { column } else { 0 };
+        writeln!(
+            out,
+            "{}:{}: {}[{}]: {}",
+            start.line + 1,
+            start.column + shift + 1,
+            problem.severity,
+            problem.code,
+            problem.message
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Repl;
+
+    /// Feed the lines to a fresh REPL and return its output without the
+    /// banner.
+    fn session(lines: &[&str]) -> String {
+        let input = lines.join("\n");
+        let mut out = Vec::new();
+        Repl::new().run(input.as_bytes(), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let (_banner, out) = out.split_once('\n').unwrap();
+        out.to_string()
+    }
+
+    #[test]
+    fn repl_eval() {
+        assert_eq!(session(&["(λx:Unit. x) unit"]), "> unit : Unit\n> \n");
+    }
+
+    #[test]
+    fn repl_definitions() {
+        assert_eq!(
+            session(&["let id = λx:Unit. x", "let u:Unit = id unit", "id u", "id",]),
+            "\
+> id : (Unit -> Unit)
+> u : Unit
+> unit : Unit
+> <closure λx> : (Unit -> Unit)
+> \n"
+        );
+    }
+
+    #[test]
+    fn repl_failed_definition() {
+        assert_eq!(
+            session(&["let f = unit unit", "f"]),
+            "\
+> error: expected a function type, found Unit
+> error: unknown variable: f
+> \n"
+        );
+    }
+
+    #[test]
+    fn repl_annotation_mismatch() {
+        assert_eq!(
+            session(&["let u:Unit -> Unit = unit"]),
+            "> error: expected type (Unit -> Unit), found Unit\n> \n"
+        );
+    }
+
+    #[test]
+    fn repl_problems() {
+        assert_eq!(
+            session(&["let x = )", ":type λ"]),
+            "\
+> 1:9: error[P0001]: Expected an expression, found `)`.
+> 1:8: error[P0001]: Expected a name, found the end of the input.
+> \n"
+        );
+    }
+
+    #[test]
+    fn repl_commands() {
+        assert_eq!(
+            session(&[
+                "let f = λx:Unit. x",
+                ":type f unit",
+                ":ast (f) (unit)",
+                ":cst f",
+                ":system",
+                ":system hm",
+                ":frobnicate",
+                ":quit",
+                "unit",
+            ]),
+            r#"> f : (Unit -> Unit)
+> f unit : Unit
+> f unit
+> PROGRAM@0..1
+  EXPR_VAR@0..1
+    ID_EXPR@0..1 "f"
+> Simply Typed Lambda Calculus
+> error: Hindley-Milner is not available yet
+> error: unknown command :frobnicate (:help for help)
+> "#
+        );
+    }
+}
@@ -0,0 +1,259 @@
+//! An evaluator for the subset of JavaScript the backend emits, so that the
+//! tests can run the generated modules without a JavaScript engine. It is
+//! strict where the backend could go wrong: reading a `const` before its
+//! initialisation and declaring a name twice in a block are errors, as they
+//! are in JavaScript.
+use std::{cell::RefCell, rc::Rc};
+
+#[derive(Clone, Debug)]
+pub enum Value {
+    Undefined,
+    Closure(Rc<Closure>),
+}
+
+#[derive(Debug)]
+pub struct Closure {
+    pub param: Option<String>,
+    body: Rc<Body>,
+    env: Env,
+}
+
+#[derive(Debug)]
+enum Expr {
+    Var(String),
+    Undefined,
+    Arrow(Option<String>, Rc<Body>),
+    Call(Box<Expr>, Option<Box<Expr>>),
+}
+
+/// The `const` declarations of a block followed by its result.
+#[derive(Debug)]
+struct Body {
+    consts: Vec<(String, Expr)>,
+    result: Expr,
+}
+
+/// A binding is `None` until its initialiser has been evaluated.
+type Binding = Rc<RefCell<Option<Value>>>;
+
+#[derive(Clone, Debug, Default)]
+struct Env(Option<Rc<(String, Binding, Env)>>);
+
+impl Env {
+    fn extend(&self, name: String, binding: Binding) -> Self {
+        Self(Some(Rc::new((name, binding, self.clone()))))
+    }
+
+    fn lookup(&self, name: &str) -> Result<Value, String> {
+        let mut env = self;
+        while let Some(node) = &env.0 {
+            let (bound, binding, next) = node.as_ref();
+            if bound == name {
+                return binding
+                    .borrow()
+                    .clone()
+                    .ok_or_else(|| format!("ReferenceError: {} before initialization", name));
+            }
+            env = next;
+        }
+        Err(format!("ReferenceError: {} is not defined", name))
+    }
+}
+
+/// Evaluate a module and return its default export.
+pub fn run(module: &str) -> Result<Value, String> {
+    let mut parser = Parser {
+        tokens: tokenize(module)?,
+        pos: 0,
+    };
+    let body = parser.body(&["export", "default"])?;
+    if parser.pos != parser.tokens.len() {
+        return Err(String::from("SyntaxError: trailing tokens"));
+    }
+    eval_body(&Env::default(), &body)
+}
+
+fn eval_body(env: &Env, body: &Body) -> Result<Value, String> {
+    let mut env = env.clone();
+    let mut declared: Vec<&str> = Vec::new();
+    for (name, init) in &body.consts {
+        if declared.contains(&name.as_str()) {
+            return Err(format!("SyntaxError: redeclaration of {}", name));
+        }
+        declared.push(name);
+        let binding = Binding::default();
+        env = env.extend(name.clone(), binding.clone());
+        let value = eval(&env, init)?;
+        *binding.borrow_mut() = Some(value);
+    }
+    eval(&env, &body.result)
+}
+
+fn eval(env: &Env, expr: &Expr) -> Result<Value, String> {
+    match expr {
+        Expr::Var(name) => env.lookup(name),
+        Expr::Undefined => Ok(Value::Undefined),
+        Expr::Arrow(param, body) => Ok(Value::Closure(Rc::new(Closure {
+            param: param.clone(),
+            body: body.clone(),
+            env: env.clone(),
+        }))),
+        Expr::Call(fun, arg) => {
+            let fun = eval(env, fun)?;
+            let arg = match arg {
+                Some(arg) => eval(env, arg)?,
+                None => Value::Undefined,
+            };
+            let Value::Closure(closure) = fun else {
+                return Err(String::from("TypeError: not a function"));
+            };
+            let env = match &closure.param {
+                Some(param) => closure
+                    .env
+                    .extend(param.clone(), Rc::new(RefCell::new(Some(arg)))),
+                None => closure.env.clone(),
+            };
+            eval_body(&env, &closure.body)
+        }
+    }
+}
+
+fn tokenize(module: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = module.trim_start();
+    while let Some(char) = rest.chars().next() {
+        let len = if char.is_ascii_alphanumeric() || char == '_' || char == '$' {
+            rest.find(|char: char| !char.is_ascii_alphanumeric() && char != '_' && char != '$')
+                .unwrap_or(rest.len())
+        } else if rest.starts_with("=>") {
+            2
+        } else if "(){}=;".contains(char) {
+            1
+        } else {
+            return Err(format!("SyntaxError: unexpected {}", char));
+        };
+        tokens.push(String::from(&rest[..len]));
+        rest = rest[len..].trim_start();
+    }
+    Ok(tokens)
+}
+
+fn is_name(token: &str) -> bool {
+    token.starts_with(|char: char| !"(){}=;".contains(char))
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self, offset: usize) -> Option<&str> {
+        self.tokens.get(self.pos + offset).map(String::as_str)
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        let found = self.peek(0) == Some(token);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), String> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(format!(
+                "SyntaxError: expected {}, found {:?}",
+                token,
+                self.peek(0)
+            ))
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, String> {
+        match self.peek(0) {
+            Some(token) if is_name(token) => {
+                self.pos += 1;
+                Ok(self.tokens[self.pos - 1].clone())
+            }
+            token => Err(format!("SyntaxError: expected a name, found {:?}", token)),
+        }
+    }
+
+    /// `const` declarations followed by the keywords introducing the
+    /// result, which is terminated by a semicolon.
+    fn body(&mut self, result: &[&str]) -> Result<Body, String> {
+        let mut consts = Vec::new();
+        while self.eat("const") {
+            let name = self.ident()?;
+            self.expect("=")?;
+            consts.push((name, self.expr()?));
+            self.expect(";")?;
+        }
+        for keyword in result {
+            self.expect(keyword)?;
+        }
+        let result = self.expr()?;
+        self.expect(";")?;
+        Ok(Body { consts, result })
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let is_arrow = match (self.peek(0), self.peek(1), self.peek(2)) {
+            (Some("("), Some(")"), Some("=>")) => true,
+            (Some("("), Some(param), Some(")")) => is_name(param) && self.peek(3) == Some("=>"),
+            _ => false,
+        };
+        if !is_arrow {
+            return self.call();
+        }
+        self.expect("(")?;
+        let param = match self.eat(")") {
+            true => None,
+            false => {
+                let param = self.ident()?;
+                self.expect(")")?;
+                Some(param)
+            }
+        };
+        self.expect("=>")?;
+        let body = if self.eat("{") {
+            let body = self.body(&["return"])?;
+            self.expect("}")?;
+            body
+        } else {
+            Body {
+                consts: Vec::new(),
+                result: self.expr()?,
+            }
+        };
+        Ok(Expr::Arrow(param, Rc::new(body)))
+    }
+
+    fn call(&mut self) -> Result<Expr, String> {
+        let mut expr = if self.eat("(") {
+            let expr = self.expr()?;
+            self.expect(")")?;
+            expr
+        } else {
+            match self.ident()?.as_str() {
+                "undefined" => Expr::Undefined,
+                name => Expr::Var(String::from(name)),
+            }
+        };
+        while self.eat("(") {
+            let arg = match self.eat(")") {
+                true => None,
+                false => {
+                    let arg = self.expr()?;
+                    self.expect(")")?;
+                    Some(Box::new(arg))
+                }
+            };
+            expr = Expr::Call(Box::new(expr), arg);
+        }
+        Ok(expr)
+    }
+}
@@ -0,0 +1,451 @@
+use std::fmt::Write;
+
+use felix_core::{Expr, Name, Prim};
+
+const INDENT: &str = "    ";
+
+/// Identifiers which are valid in felix but cannot be used as binding names
+/// in JavaScript.
+const RESERVED: &[&str] = &[
+    "arguments",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "eval",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "undefined",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+/// Compile a closed, type-checked expression into an ES2020 module whose
+/// default export is the value of the expression.
+pub fn compile(expr: &Expr) -> String {
+    let mut codegen = Codegen::default();
+    let mut out = String::new();
+    let result = codegen.stmts(expr, 0, &mut out);
+    writeln!(out, "export default {};", result).unwrap();
+    out
+}
+
+#[derive(Default)]
+struct Codegen {
+    /// The felix identifiers currently in scope together with the names of
+    /// the JavaScript variables they are bound to.
+    scope: Vec<(Name, String)>,
+}
+
+impl Codegen {
+    fn lookup(&self, ident: &Name) -> String {
+        self.scope
+            .iter()
+            .rev()
+            .find(|(bound, _)| bound == ident)
+            .map(|(_, name)| name.clone())
+            .unwrap_or_else(|| mangle(ident))
+    }
+
+    fn is_taken(&self, name: &str) -> bool {
+        self.scope.iter().any(|(_, taken)| taken == name)
+    }
+
+    /// Bring a lambda parameter into scope. Parameters live in their own
+    /// scope in JavaScript and hence can shadow whatever they like.
+    fn bind_param(&mut self, ident: &Name) -> String {
+        let name = mangle(ident);
+        self.scope.push((ident.clone(), name.clone()));
+        name
+    }
+
+    /// Bring a `const` into scope. Since JavaScript does not allow
+    /// redeclaring a name within a block and puts the bound name into scope
+    /// of its own initializer, we rename the binding whenever the name is
+    /// already in use.
+    fn bind_const(&mut self, ident: &Name) -> String {
+        let base = mangle(ident);
+        let mut name = base.clone();
+        let mut counter = 0;
+        while self.is_taken(&name) {
+            counter += 1;
+            name = format!("{}${}", base, counter);
+        }
+        self.scope.push((ident.clone(), name.clone()));
+        name
+    }
+
+    fn unbind(&mut self, count: usize) {
+        self.scope.truncate(self.scope.len() - count);
+    }
+
+    /// Emit a `const` declaration for every `let` at the spine of `expr` and
+    /// return the JavaScript expression for the final body.
+    fn stmts(&mut self, expr: &Expr, indent: usize, out: &mut String) -> String {
+        let mut expr = expr.strip_ticks();
+        let mut bound = 0;
+        while let Expr::Let(name, bindee, body) = expr {
+            let bindee = self.expr(bindee, indent);
+            let name = self.bind_const(name);
+            bound += 1;
+            writeln!(out, "{}const {} = {};", INDENT.repeat(indent), name, bindee).unwrap();
+            expr = body.strip_ticks();
+        }
+        let result = self.expr(expr, indent);
+        self.unbind(bound);
+        result
+    }
+
+    /// Render `expr` as the statements of a function body, followed by the
+    /// closing brace at `indent - 1`.
+    fn block(&mut self, expr: &Expr, indent: usize) -> String {
+        let mut out = String::from("{\n");
+        let result = self.stmts(expr, indent, &mut out);
+        writeln!(out, "{}return {};", INDENT.repeat(indent), result).unwrap();
+        write!(out, "{}}}", INDENT.repeat(indent - 1)).unwrap();
+        out
+    }
+
+    fn expr(&mut self, expr: &Expr, indent: usize) -> String {
+        match expr {
+            Expr::Var(name) => self.lookup(name),
+            Expr::Abs(param, body) => {
+                let param = self.bind_param(param);
+                let body = match body.strip_ticks() {
+                    Expr::Let(..) => self.block(body, indent + 1),
+                    _ => self.expr(body, indent),
+                };
+                self.unbind(1);
+                format!("({}) => {}", param, body)
+            }
+            Expr::App(fun, arg) => {
+                let rendered_fun = self.expr(fun, indent);
+                let arg = self.expr(arg, indent);
+                match fun.strip_ticks() {
+                    Expr::Abs(..) => format!("({})({})", rendered_fun, arg),
+                    _ => format!("{}({})", rendered_fun, arg),
+                }
+            }
+            // NOTE: A `let` in expression position becomes an immediately
+            // invoked arrow function.
+            Expr::Let(..) => format!("(() => {})()", self.block(expr, indent + 1)),
+            Expr::Prim(Prim::Unit) => String::from("undefined"),
+            Expr::Tick(_, expr) => self.expr(expr, indent),
+        }
+    }
+}
+
+fn mangle(ident: &Name) -> String {
+    let name = ident.as_ref();
+    if RESERVED.contains(&name) {
+        format!("{}$", name)
+    } else {
+        String::from(name)
+    }
+}
+
+#[cfg(test)]
+mod js;
+
+#[cfg(test)]
+mod tests {
+    use insta::assert_snapshot;
+
+    use felix_core::*;
+
+    use super::{compile, js, mangle};
+
+    #[test]
+    fn js_var() {
+        assert_snapshot!(compile(&var("x")), @"export default x;");
+    }
+
+    #[test]
+    fn js_unit() {
+        assert_snapshot!(compile(&unit()), @"export default undefined;");
+    }
+
+    #[test]
+    fn js_abs_plain() {
+        assert_snapshot!(compile(&abs("x", var("x"))), @"export default (x) => x;");
+    }
+
+    #[test]
+    fn js_abs_abs() {
+        let expr = abs("x", abs("y", var("x")));
+        assert_snapshot!(compile(&expr), @"export default (x) => (y) => x;");
+    }
+
+    #[test]
+    fn js_app() {
+        let expr = app(app(var("f"), var("x")), var("y"));
+        assert_snapshot!(compile(&expr), @"export default f(x)(y);");
+    }
+
+    #[test]
+    fn js_app_abs_fun() {
+        let expr = app(abs("x", var("x")), unit());
+        assert_snapshot!(compile(&expr), @"export default ((x) => x)(undefined);");
+    }
+
+    #[test]
+    fn js_app_abs_arg() {
+        let expr = app(var("f"), abs("x", var("x")));
+        assert_snapshot!(compile(&expr), @"export default f((x) => x);");
+    }
+
+    #[test]
+    fn js_let_top_level() {
+        let expr = let_("x", unit(), let_("y", var("x"), var("y")));
+        assert_snapshot!(compile(&expr), @r"
+        const x = undefined;
+        const y = x;
+        export default y;
+        ");
+    }
+
+    #[test]
+    fn js_let_in_abs() {
+        let expr = abs("x", let_("y", var("x"), var("y")));
+        assert_snapshot!(compile(&expr), @r"
+        export default (x) => {
+            const y = x;
+            return y;
+        };
+        ");
+    }
+
+    #[test]
+    fn js_let_in_arg() {
+        let expr = app(var("f"), let_("x", unit(), var("x")));
+        assert_snapshot!(compile(&expr), @r"
+        export default f((() => {
+            const x = undefined;
+            return x;
+        })());
+        ");
+    }
+
+    #[test]
+    fn js_let_in_bindee_nested() {
+        let expr = let_("f", abs("x", let_("y", var("x"), var("y"))), var("f"));
+        assert_snapshot!(compile(&expr), @r"
+        const f = (x) => {
+            const y = x;
+            return y;
+        };
+        export default f;
+        ");
+    }
+
+    #[test]
+    fn js_let_shadowing_let() {
+        let expr = let_("x", unit(), let_("x", var("x"), var("x")));
+        assert_snapshot!(compile(&expr), @r"
+        const x = undefined;
+        const x$1 = x;
+        export default x$1;
+        ");
+    }
+
+    #[test]
+    fn js_let_shadowing_param() {
+        let expr = abs("x", let_("x", var("x"), var("x")));
+        assert_snapshot!(compile(&expr), @r"
+        export default (x) => {
+            const x$1 = x;
+            return x$1;
+        };
+        ");
+    }
+
+    #[test]
+    fn js_let_shadowing_renamed() {
+        let expr = let_(
+            "x",
+            unit(),
+            let_("x", var("x"), let_("x", var("x"), var("x"))),
+        );
+        assert_snapshot!(compile(&expr), @r"
+        const x = undefined;
+        const x$1 = x;
+        const x$2 = x$1;
+        export default x$2;
+        ");
+    }
+
+    #[test]
+    fn js_param_shadowing_let() {
+        let expr = let_("x", unit(), abs("x", var("x")));
+        assert_snapshot!(compile(&expr), @r"
+        const x = undefined;
+        export default (x) => x;
+        ");
+    }
+
+    #[test]
+    fn js_shadowing_ends_with_scope() {
+        let expr = let_(
+            "f",
+            abs("x", let_("x", var("x"), var("x"))),
+            let_("x", var("f"), var("x")),
+        );
+        assert_snapshot!(compile(&expr), @r"
+        const f = (x) => {
+            const x$1 = x;
+            return x$1;
+        };
+        const x = f;
+        export default x;
+        ");
+    }
+
+    #[test]
+    fn js_reserved_words() {
+        let expr = let_("new", unit(), abs("this", var("new")));
+        assert_snapshot!(compile(&expr), @r"
+        const new$ = undefined;
+        export default (this$) => new$;
+        ");
+    }
+
+    #[test]
+    fn js_ticks_are_transparent() {
+        let tick = |expr| Expr::Tick(Default::default(), std::rc::Rc::new(expr));
+        let expr = tick(let_(
+            "f",
+            tick(abs("x", tick(let_("y", tick(var("x")), tick(var("y")))))),
+            tick(app(tick(abs("z", var("z"))), tick(var("f")))),
+        ));
+        assert_snapshot!(compile(&expr), @r"
+        const f = (x) => {
+            const y = x;
+            return y;
+        };
+        export default ((z) => z)(f);
+        ");
+    }
+
+    #[test]
+    fn js_twice() {
+        let expr = let_(
+            "twice",
+            abs("f", abs("u", app(var("f"), app(var("f"), var("u"))))),
+            app(app(var("twice"), abs("u", var("u"))), unit()),
+        );
+        assert_snapshot!(compile(&expr), @r"
+        const twice = (f) => (u) => f(f(u));
+        export default twice((u) => u)(undefined);
+        ");
+    }
+
+    #[test]
+    fn agrees_with_interpreter() {
+        let tick = |expr| Expr::Tick(Default::default(), std::rc::Rc::new(expr));
+        let two = abs("f", abs("x", app(var("f"), app(var("f"), var("x")))));
+        let step = abs("k", abs("u", app(var("k"), var("u"))));
+        let corpus = vec![
+            unit(),
+            abs("x", var("x")),
+            app(abs("x", var("x")), unit()),
+            app(abs("x", app(var("x"), unit())), abs("y", unit())),
+            let_("x", unit(), let_("x", abs("y", var("x")), var("x"))),
+            let_(
+                "x",
+                abs("a", var("a")),
+                app(let_("x", abs("b", var("x")), var("x")), unit()),
+            ),
+            let_("x", abs("a", var("a")), app(abs("x", var("x")), unit())),
+            app(abs("x", let_("x", var("x"), var("x"))), abs("c", unit())),
+            let_(
+                "f",
+                abs("x", let_("x", var("x"), var("x"))),
+                let_("x", var("f"), app(var("x"), abs("d", var("d")))),
+            ),
+            app(abs("y", var("y")), let_("x", abs("e", var("e")), var("x"))),
+            let_(
+                "new",
+                abs("this", var("this")),
+                app(var("new"), abs("var", unit())),
+            ),
+            let_(
+                "tru",
+                abs("t", abs("f", var("t"))),
+                let_(
+                    "fls",
+                    abs("u", abs("v", var("v"))),
+                    let_(
+                        "not",
+                        abs("b", app(app(var("b"), var("fls")), var("tru"))),
+                        app(var("not"), app(var("not"), var("fls"))),
+                    ),
+                ),
+            ),
+            let_(
+                "two",
+                two,
+                app(
+                    app(app(app(var("two"), var("two")), step), abs("u", var("u"))),
+                    unit(),
+                ),
+            ),
+            tick(let_(
+                "f",
+                tick(abs("x", tick(let_("y", tick(var("x")), tick(var("y")))))),
+                tick(app(tick(var("f")), tick(abs("z", var("z"))))),
+            )),
+        ];
+        for expr in corpus {
+            let expected = felix_interpreter::eval(&expr).unwrap();
+            let module = compile(&expr);
+            let actual = js::run(&module).unwrap_or_else(|error| panic!("{}\n{}", error, module));
+            match (&expected, &actual) {
+                (felix_interpreter::Value::Unit, js::Value::Undefined) => {}
+                (felix_interpreter::Value::Closure(expected), js::Value::Closure(actual))
+                    if actual.param.as_deref() == Some(mangle(&expected.param).as_str()) => {}
+                _ => panic!(
+                    "{}: interpreter gives {:?}, JavaScript gives {:?} for\n{}",
+                    expr, expected, actual, module
+                ),
+            }
+        }
+    }
+}
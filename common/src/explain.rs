@@ -0,0 +1,120 @@
+//! Long-form explanations of problem codes. Each explanation is a Markdown
+//! document with examples in fenced code blocks. Blocks tagged
+//! `felix,fails` contain programs reporting the code and blocks tagged
+//! `felix,passes` the same programs with the problem fixed. Blocks tagged
+//! `match,fails` and `match,passes` do the same for matches.
+use crate::Code;
+
+/// The language of an example.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Language {
+    Felix,
+    /// A match in the syntax of the match compiler's tests: `match` and
+    /// the type of the scrutinee, followed by one arm per line. Matches are
+    /// not part of felix yet.
+    Match,
+}
+
+/// An example program from an explanation.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Example {
+    pub language: Language,
+    pub source: &'static str,
+    /// Whether the program reports the code that is explained.
+    pub fails: bool,
+}
+
+pub fn explain(code: Code) -> &'static str {
+    match code {
+        Code::UNEXPECTED_TOKEN => include_str!("explanations/P0001.md"),
+        Code::CHAINED_OPERATORS => include_str!("explanations/P0002.md"),
+        Code::UNEXPECTED_CHARACTER => include_str!("explanations/P0003.md"),
+        Code::ILL_TYPED_PATTERN => include_str!("explanations/M0001.md"),
+        Code::REDUNDANT_ARM => include_str!("explanations/M0002.md"),
+        Code::NON_EXHAUSTIVE_MATCH => include_str!("explanations/M0003.md"),
+        Code::UNKNOWN_VARIABLE => include_str!("explanations/T0001.md"),
+        Code::CANNOT_INFER => include_str!("explanations/T0002.md"),
+        Code::EXPECTED_FUNCTION => include_str!("explanations/T0003.md"),
+        Code::TYPE_MISMATCH => include_str!("explanations/T0004.md"),
+    }
+}
+
+/// The title of the explanation of the code, e.g. "Unexpected token".
+pub fn title(code: Code) -> &'static str {
+    let line = explain(code).lines().next().unwrap_or_default();
+    line.split_once(": ").map_or(line, |(_, title)| title)
+}
+
+/// The examples in the explanation of the code, in order. Code blocks in
+/// other languages are skipped.
+pub fn examples(code: Code) -> Vec<Example> {
+    const FENCE: &str = "```";
+    let mut examples = Vec::new();
+    let mut rest = explain(code);
+    while let Some(start) = rest.find(FENCE) {
+        let (info, body) = rest[start + FENCE.len()..]
+            .split_once('\n')
+            .expect("code block without body");
+        let end = body.find(FENCE).expect("unterminated code block");
+        rest = &body[end + FENCE.len()..];
+        let (language, tag) = match info.trim_end().split_once(',') {
+            Some(("felix", tag)) => (Language::Felix, tag),
+            Some(("match", tag)) => (Language::Match, tag),
+            _ => continue,
+        };
+        let fails = match tag {
+            "fails" => true,
+            "passes" => false,
+            tag => panic!("unknown tag {} in explanation of {}", tag, code),
+        };
+        examples.push(Example {
+            language,
+            source: &body[..end],
+            fails,
+        });
+    }
+    examples
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explanations_start_with_code() {
+        for code in Code::ALL {
+            let title = format!("# {}: ", code);
+            assert!(explain(code).starts_with(&title), "code: {}", code);
+            assert!(!super::title(code).is_empty(), "code: {}", code);
+            assert_eq!(code.as_str().parse(), Ok(code));
+        }
+    }
+
+    #[test]
+    fn test_examples() {
+        let examples = examples(Code::UNEXPECTED_TOKEN);
+        assert_eq!(
+            examples.first(),
+            Some(&Example {
+                language: Language::Felix,
+                source: "let id = λx:Unit. in id unit\n",
+                fails: true,
+            })
+        );
+        assert_eq!(
+            examples
+                .iter()
+                .map(|example| example.fails)
+                .collect::<Vec<_>>(),
+            vec![true, false, true, false]
+        );
+        let examples = super::examples(Code::REDUNDANT_ARM);
+        assert_eq!(
+            examples
+                .iter()
+                .map(|example| (example.language, example.fails))
+                .collect::<Vec<_>>(),
+            vec![(Language::Match, true), (Language::Match, false)]
+        );
+    }
+}
@@ -1,5 +1,7 @@
+pub mod explain;
 pub mod problem;
+pub mod render;
 pub mod srcloc;
 
-pub use problem::{Problem, Severity};
+pub use problem::{Code, Problem, Severity};
 pub use srcloc::{SrcLoc, SrcSpan};
@@ -1,12 +1,132 @@
 use serde::Serialize;
 use tsify_next::Tsify;
 
-use crate::{srcloc::Mapper, SrcLoc};
+use crate::{srcloc::Mapper, SrcLoc, SrcSpan};
 
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Tsify)]
 #[tsify(into_wasm_abi)]
 pub enum Severity {
     ERROR,
+    WARNING,
+    INFO,
+    HINT,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::ERROR => "error",
+            Self::WARNING => "warning",
+            Self::INFO => "info",
+            Self::HINT => "hint",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Stable codes for the kinds of problems. A code never changes its meaning
+/// once it has been released, so that users can search for it. The letter
+/// names the phase reporting the problem.
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+pub enum Code {
+    #[serde(rename = "P0001")]
+    UNEXPECTED_TOKEN,
+    #[serde(rename = "P0002")]
+    CHAINED_OPERATORS,
+    #[serde(rename = "P0003")]
+    UNEXPECTED_CHARACTER,
+    #[serde(rename = "M0001")]
+    ILL_TYPED_PATTERN,
+    #[serde(rename = "M0002")]
+    REDUNDANT_ARM,
+    #[serde(rename = "M0003")]
+    NON_EXHAUSTIVE_MATCH,
+    #[serde(rename = "T0001")]
+    UNKNOWN_VARIABLE,
+    #[serde(rename = "T0002")]
+    CANNOT_INFER,
+    #[serde(rename = "T0003")]
+    EXPECTED_FUNCTION,
+    #[serde(rename = "T0004")]
+    TYPE_MISMATCH,
+}
+
+impl Code {
+    pub const ALL: [Self; 10] = [
+        Self::UNEXPECTED_TOKEN,
+        Self::CHAINED_OPERATORS,
+        Self::UNEXPECTED_CHARACTER,
+        Self::ILL_TYPED_PATTERN,
+        Self::REDUNDANT_ARM,
+        Self::NON_EXHAUSTIVE_MATCH,
+        Self::UNKNOWN_VARIABLE,
+        Self::CANNOT_INFER,
+        Self::EXPECTED_FUNCTION,
+        Self::TYPE_MISMATCH,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::UNEXPECTED_TOKEN => "P0001",
+            Self::CHAINED_OPERATORS => "P0002",
+            Self::UNEXPECTED_CHARACTER => "P0003",
+            Self::ILL_TYPED_PATTERN => "M0001",
+            Self::REDUNDANT_ARM => "M0002",
+            Self::NON_EXHAUSTIVE_MATCH => "M0003",
+            Self::UNKNOWN_VARIABLE => "T0001",
+            Self::CANNOT_INFER => "T0002",
+            Self::EXPECTED_FUNCTION => "T0003",
+            Self::TYPE_MISMATCH => "T0004",
+        }
+    }
+}
+
+impl std::fmt::Display for Code {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Code {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|code| code.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown problem code: {}", s))
+    }
+}
+
+/// A secondary span with a message explaining its part in a problem.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+pub struct Label {
+    pub start: SrcLoc,
+    pub end: SrcLoc,
+    pub span: SrcSpan<u32>,
+    pub message: String,
+}
+
+/// Replace the text in a span.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+pub struct TextEdit {
+    pub start: SrcLoc,
+    pub end: SrcLoc,
+    pub span: SrcSpan<u32>,
+    pub text: String,
+}
+
+/// A fix for a problem which tools can apply without asking the user for
+/// more input.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+pub struct Suggestion {
+    pub message: String,
+    pub edits: Vec<TextEdit>,
 }
 
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Tsify)]
@@ -14,32 +134,156 @@ pub enum Severity {
 pub struct Problem {
     pub start: SrcLoc,
     pub end: SrcLoc,
+    /// The byte indices `start` and `end` stem from.
+    pub span: SrcSpan<u32>,
     pub severity: Severity,
+    pub code: Code,
     pub source: String,
     pub message: String,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    pub suggestions: Vec<Suggestion>,
+    /// The names of the tokens the parser expected when it reports an
+    /// unexpected token, and empty otherwise.
+    pub expected: Vec<String>,
+}
+
+impl Problem {
+    pub fn with_label(mut self, label: Label) -> Self {
+        self.labels.push(label);
+        self
+    }
+
+    pub fn with_note(mut self, note: String) -> Self {
+        self.notes.push(note);
+        self
+    }
+
+    pub fn with_suggestion(mut self, suggestion: Suggestion) -> Self {
+        self.suggestions.push(suggestion);
+        self
+    }
 }
 
-impl<'a> Mapper<'a> {
+impl Mapper {
     pub fn problem(
         &self,
         start: u32,
         end: u32,
         severity: Severity,
+        code: Code,
         source: String,
         message: String,
     ) -> Problem {
+        let span = SrcSpan { start, end };
         let start = self.src_loc(start);
         let end = self.src_loc(end);
         Problem {
             start,
             end,
+            span,
             severity,
+            code,
             source,
             message,
+            labels: Vec::new(),
+            notes: Vec::new(),
+            suggestions: Vec::new(),
+            expected: Vec::new(),
+        }
+    }
+
+    pub fn error(
+        &self,
+        start: u32,
+        end: u32,
+        code: Code,
+        source: String,
+        message: String,
+    ) -> Problem {
+        self.problem(start, end, Severity::ERROR, code, source, message)
+    }
+
+    pub fn warning(
+        &self,
+        start: u32,
+        end: u32,
+        code: Code,
+        source: String,
+        message: String,
+    ) -> Problem {
+        self.problem(start, end, Severity::WARNING, code, source, message)
+    }
+
+    pub fn label(&self, span: SrcSpan<u32>, message: String) -> Label {
+        Label {
+            start: self.src_loc(span.start),
+            end: self.src_loc(span.end),
+            span,
+            message,
+        }
+    }
+
+    pub fn text_edit(&self, span: SrcSpan<u32>, text: String) -> TextEdit {
+        TextEdit {
+            start: self.src_loc(span.start),
+            end: self.src_loc(span.end),
+            span,
+            text,
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    pub fn error(&self, start: u32, end: u32, source: String, message: String) -> Problem {
-        self.problem(start, end, Severity::ERROR, source, message)
+    #[test]
+    fn test_serialize() {
+        let mapper = Mapper::new("λx.\nx x");
+        let problem = mapper
+            .warning(
+                7,
+                8,
+                Code::REDUNDANT_ARM,
+                String::from("match"),
+                String::from("Redundant arm."),
+            )
+            .with_label(mapper.label(SrcSpan { start: 5, end: 6 }, String::from("earlier")))
+            .with_note(String::from("Remove it."))
+            .with_suggestion(Suggestion {
+                message: String::from("Remove the arm"),
+                edits: vec![mapper.text_edit(SrcSpan { start: 6, end: 8 }, String::new())],
+            });
+        let loc = |line, column| serde_json::json!({"line": line, "column": column});
+        assert_eq!(
+            serde_json::to_value(&problem).unwrap(),
+            serde_json::json!({
+                "start": loc(1, 2),
+                "end": loc(1, 3),
+                "span": {"start": 7, "end": 8},
+                "severity": "WARNING",
+                "code": "M0002",
+                "source": "match",
+                "message": "Redundant arm.",
+                "labels": [{
+                    "start": loc(1, 0),
+                    "end": loc(1, 1),
+                    "span": {"start": 5, "end": 6},
+                    "message": "earlier",
+                }],
+                "notes": ["Remove it."],
+                "suggestions": [{
+                    "message": "Remove the arm",
+                    "edits": [{
+                        "start": loc(1, 1),
+                        "end": loc(1, 3),
+                        "span": {"start": 6, "end": 8},
+                        "text": "",
+                    }],
+                }],
+                "expected": [],
+            })
+        );
     }
 }
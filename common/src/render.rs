@@ -0,0 +1,348 @@
+//! Rendering of problems for humans reading them in a terminal, in the
+//! style of rustc: a header with the message, the location and the source
+//! lines involved with the spans underlined, followed by notes.
+use std::fmt::Write;
+
+use unicode_width::UnicodeWidthChar;
+
+use crate::{srcloc::Mapper, Problem, Severity, SrcSpan};
+
+/// Tabs are rendered as this many spaces.
+const TAB_WIDTH: usize = 4;
+
+const BOLD: &str = "1";
+const RED: &str = "1;31";
+const YELLOW: &str = "1;33";
+const BLUE: &str = "1;34";
+const CYAN: &str = "1;36";
+
+pub struct Renderer<'a> {
+    /// The name of the input used in locations.
+    name: &'a str,
+    mapper: &'a Mapper,
+    color: bool,
+}
+
+/// A span to underline together with the message to put after it.
+struct Annotation<'a> {
+    span: SrcSpan<u32>,
+    primary: bool,
+    message: &'a str,
+}
+
+/// The part of an annotation on a single line, in display columns.
+struct Segment<'a> {
+    start: usize,
+    end: usize,
+    primary: bool,
+    /// Only the segment on the last line of an annotation has a message.
+    message: Option<&'a str>,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(name: &'a str, mapper: &'a Mapper) -> Self {
+        Self {
+            name,
+            mapper,
+            color: false,
+        }
+    }
+
+    /// Use ANSI escape codes to colour the output.
+    pub fn with_color(self, color: bool) -> Self {
+        Self { color, ..self }
+    }
+
+    pub fn render(&self, problem: &Problem) -> String {
+        let mut out = String::new();
+        self.render_to(problem, &mut out)
+            .expect("writing to a string cannot fail");
+        out
+    }
+
+    fn render_to(&self, problem: &Problem, out: &mut String) -> std::fmt::Result {
+        let severity_style = match problem.severity {
+            Severity::ERROR => RED,
+            Severity::WARNING => YELLOW,
+            Severity::INFO => BLUE,
+            Severity::HINT => CYAN,
+        };
+        writeln!(
+            out,
+            "{}{}",
+            self.paint(
+                severity_style,
+                &format!("{}[{}]", problem.severity, problem.code)
+            ),
+            self.paint(BOLD, &format!(": {}", problem.message)),
+        )?;
+
+        let annotations: Vec<_> = std::iter::once(Annotation {
+            span: problem.span,
+            primary: true,
+            message: "",
+        })
+        .chain(problem.labels.iter().map(|label| Annotation {
+            span: label.span,
+            primary: false,
+            message: &label.message,
+        }))
+        .collect();
+        let mut lines: Vec<(u32, Vec<Segment>)> = Vec::new();
+        for annotation in &annotations {
+            for (line, segment) in self.segments(annotation) {
+                match lines.iter_mut().find(|(other, _)| *other == line) {
+                    Some((_, segments)) => segments.push(segment),
+                    None => lines.push((line, vec![segment])),
+                }
+            }
+        }
+        lines.sort_by_key(|(line, _)| *line);
+
+        let last_line = lines.last().map_or(0, |(line, _)| line + 1);
+        let gutter = " ".repeat(last_line.to_string().len());
+        let bar = self.paint(BLUE, "|");
+        // NOTE: Locations are one-based for humans.
+        writeln!(
+            out,
+            "{}{} {}:{}:{}",
+            gutter,
+            self.paint(BLUE, "-->"),
+            self.name,
+            problem.start.line + 1,
+            problem.start.column + 1
+        )?;
+        writeln!(out, "{} {}", gutter, bar)?;
+        let mut previous = None;
+        for (line, segments) in &mut lines {
+            if previous.is_some_and(|previous| previous + 1 < *line) {
+                writeln!(out, "{}", self.paint(BLUE, "..."))?;
+            }
+            previous = Some(*line);
+            let text = self.line_text(*line);
+            let number = format!("{:>width$}", *line + 1, width = gutter.len());
+            let row = format!(
+                "{} {} {}",
+                self.paint(BLUE, &number),
+                bar,
+                expand_tabs(&text)
+            );
+            writeln!(out, "{}", row.trim_end())?;
+            segments.sort_by_key(|segment| (!segment.primary, segment.start));
+            for segment in segments.iter() {
+                let (marker, style) = if segment.primary {
+                    ("^", severity_style)
+                } else {
+                    ("-", BLUE)
+                };
+                let mut underline = marker.repeat(segment.end - segment.start);
+                if let Some(message) = segment.message.filter(|message| !message.is_empty()) {
+                    underline.push(' ');
+                    underline.push_str(message);
+                }
+                writeln!(
+                    out,
+                    "{} {} {}{}",
+                    gutter,
+                    bar,
+                    " ".repeat(segment.start),
+                    self.paint(style, &underline)
+                )?;
+            }
+        }
+        for note in &problem.notes {
+            writeln!(out, "{} {} note: {}", gutter, self.paint(BLUE, "="), note)?;
+        }
+        for suggestion in &problem.suggestions {
+            writeln!(
+                out,
+                "{} {} help: {}",
+                gutter,
+                self.paint(BLUE, "="),
+                suggestion.message
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Split an annotation into the segments on the lines it spans.
+    fn segments<'b>(&self, annotation: &Annotation<'b>) -> Vec<(u32, Segment<'b>)> {
+        let SrcSpan { start, end } = annotation.span;
+        let first = self.mapper.line_of(start);
+        let mut last = self.mapper.line_of(end);
+        // NOTE: A span ending right after a line break does not cover
+        // anything on the line after it.
+        if last > first && self.mapper.line_span(last).map(|span| span.start) == Some(end) {
+            last -= 1;
+        }
+        (first..=last)
+            .map(|line| {
+                let line_span = self.mapper.line_span(line).unwrap_or_default();
+                let text = self.line_text(line);
+                let from = start.max(line_span.start) - line_span.start;
+                let to = (end.min(line_span.end) - line_span.start).max(from);
+                let from = width(&text[..clamp(&text, from)]);
+                let to = width(&text[..clamp(&text, to)]);
+                // NOTE: Empty spans, e.g. at the end of the input, are
+                // still marked with a single character.
+                let segment = Segment {
+                    start: from,
+                    end: to.max(from + 1),
+                    primary: annotation.primary,
+                    message: (line == last).then_some(annotation.message),
+                };
+                (line, segment)
+            })
+            .collect()
+    }
+
+    fn line_text(&self, line: u32) -> String {
+        let span = self.mapper.line_span(line).unwrap_or_default();
+        let text = &self.mapper.text()[span.into_range()];
+        text.strip_suffix('\r').unwrap_or(text).to_string()
+    }
+
+    fn paint(&self, style: &str, text: &str) -> String {
+        if self.color {
+            format!("\x1b[{}m{}\x1b[0m", style, text)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+/// Clamp a byte offset into `text` to a character boundary at or before it.
+fn clamp(text: &str, offset: u32) -> usize {
+    let mut offset = (offset as usize).min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+fn char_width(char: char) -> usize {
+    if char == '\t' {
+        TAB_WIDTH
+    } else {
+        char.width().unwrap_or(0)
+    }
+}
+
+/// The number of columns the text takes up in a terminal.
+fn width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+fn expand_tabs(text: &str) -> String {
+    text.replace('\t', &" ".repeat(TAB_WIDTH))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{problem::Suggestion, Code};
+
+    fn problem(mapper: &Mapper, start: u32, end: u32) -> Problem {
+        mapper.error(
+            start,
+            end,
+            Code::UNEXPECTED_TOKEN,
+            String::from("parser"),
+            String::from("Expected a name, found `)`."),
+        )
+    }
+
+    #[test]
+    fn test_render_single_line() {
+        let mapper = Mapper::new("let x =\n  λ) in x");
+        let problem = problem(&mapper, 12, 13).with_note(String::from("A lambda binds a name."));
+        assert_eq!(
+            Renderer::new("test.fx", &mapper).render(&problem),
+            "\
+error[P0001]: Expected a name, found `)`.
+ --> test.fx:2:4
+  |
+2 |   λ) in x
+  |    ^
+  = note: A lambda binds a name.
+"
+        );
+    }
+
+    #[test]
+    fn test_render_labels_and_wide_chars() {
+        let mapper = Mapper::new("# 𝔸\n\t全x. )\n\nunit");
+        let problem = problem(&mapper, 14, 15)
+            .with_label(mapper.label(SrcSpan { start: 8, end: 12 }, String::from("here")))
+            .with_label(mapper.label(SrcSpan { start: 17, end: 21 }, String::from("there")))
+            .with_suggestion(Suggestion {
+                message: String::from("Remove the parenthesis"),
+                edits: vec![mapper.text_edit(SrcSpan { start: 14, end: 15 }, String::new())],
+            });
+        assert_eq!(
+            Renderer::new("test.fx", &mapper).render(&problem),
+            "\
+error[P0001]: Expected a name, found `)`.
+ --> test.fx:2:6
+  |
+2 |     全x. )
+  |          ^
+  |     --- here
+...
+4 | unit
+  | ---- there
+  = help: Remove the parenthesis
+"
+        );
+    }
+
+    #[test]
+    fn test_render_multi_line_span() {
+        let mapper = Mapper::new("let f = λx.\n  x\nin\nf");
+        let problem = problem(&mapper, 8, 17)
+            .with_label(mapper.label(SrcSpan { start: 0, end: 20 }, String::from("in this let")));
+        assert_eq!(
+            Renderer::new("test.fx", &mapper).render(&problem),
+            "\
+error[P0001]: Expected a name, found `)`.
+ --> test.fx:1:9
+  |
+1 | let f = λx.
+  |         ^^^
+  | -----------
+2 |   x
+  | ^^^
+  | ---
+3 | in
+  | -- in this let
+"
+        );
+    }
+
+    #[test]
+    fn test_render_end_of_input() {
+        let mapper = Mapper::new("λx.\n");
+        let problem = problem(&mapper, 5, 5);
+        assert_eq!(
+            Renderer::new("test.fx", &mapper).render(&problem),
+            "\
+error[P0001]: Expected a name, found `)`.
+ --> test.fx:2:1
+  |
+2 |
+  | ^
+"
+        );
+    }
+
+    #[test]
+    fn test_render_color() {
+        let mapper = Mapper::new("x");
+        let problem = problem(&mapper, 0, 1);
+        let output = Renderer::new("test.fx", &mapper)
+            .with_color(true)
+            .render(&problem);
+        assert!(output.starts_with("\x1b[1;31merror[P0001]\x1b[0m\x1b[1m: Expected"));
+        assert!(output.contains("\x1b[1;31m^\x1b[0m"));
+    }
+}
@@ -1,7 +1,7 @@
 use serde::Serialize;
 use tsify_next::Tsify;
 
-#[derive(Clone, Copy, Default, Eq, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize, Tsify)]
 pub struct SrcSpan<L> {
     pub start: L,
     pub end: L,
@@ -20,59 +20,163 @@ impl SrcLoc {
     }
 }
 
+/// The unit columns of source locations are counted in.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Encoding {
+    /// Bytes of the UTF-8 encoding, as used by most tools.
+    Utf8,
+    /// Code units of the UTF-16 encoding, as used by JavaScript and LSP.
+    Utf16,
+    /// Unicode scalar values.
+    #[default]
+    Utf32,
+}
+
+impl Encoding {
+    fn width(self, char: char) -> u32 {
+        match self {
+            Self::Utf8 => char.len_utf8() as u32,
+            Self::Utf16 => char.len_utf16() as u32,
+            Self::Utf32 => 1,
+        }
+    }
+}
+
 /// Mapper between byte indices into the source buffer and source locations
-/// in the form of line/column pairs.
-#[derive(Debug, Eq, PartialEq)]
-pub struct Mapper<'a> {
-    input: &'a str,
+/// in the form of line/column pairs. Columns are counted in the mapper's
+/// encoding, which defaults to Unicode scalar values.
+///
+/// The mapper owns the source text. Edits to the text via `Self::edit`
+/// update the line index in place rather than rescanning the whole text.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Mapper {
+    input: String,
     line_starts: Vec<u32>,
+    encoding: Encoding,
 }
 
-impl<'a> Mapper<'a> {
-    pub fn new(input: &'a str) -> Self {
-        if input.len() == 0 {
-            // NOTE(MH): Handling the empty input special removes an edge case
-            // from `Self::src_loc`.
-            return Self {
-                input,
-                line_starts: vec![0],
-            };
-        }
+impl Mapper {
+    pub fn new(input: impl Into<String>) -> Self {
+        let input = input.into();
         // NOTE(MH): Because we ensure that `input.len()` fits into a u32, all
         // casts into u32 below do not truncate.
-        let input_len: u32 = input.len().try_into().expect("input too long");
-        let mut line_starts: Vec<u32> = input
-            .split_inclusive('\n')
-            .scan(0, |index, line| {
-                let res = *index;
-                *index += line.len() as u32;
-                Some(res)
-            })
-            .collect();
-        if input.ends_with("\n") {
-            line_starts.push(input_len);
+        let _: u32 = input.len().try_into().expect("input too long");
+        let line_starts = std::iter::once(0).chain(line_breaks(&input, 0)).collect();
+        Self {
+            input,
+            line_starts,
+            encoding: Encoding::default(),
         }
-        Self { input, line_starts }
     }
 
-    pub fn src_loc(&self, index: u32) -> SrcLoc {
-        let line = self
-            .line_starts
+    /// The source text.
+    pub fn text(&self) -> &str {
+        &self.input
+    }
+
+    pub fn with_encoding(self, encoding: Encoding) -> Self {
+        Self { encoding, ..self }
+    }
+
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// The zero-based line the byte index is on. Indices beyond the end of
+    /// the input are on the last line.
+    pub fn line_of(&self, index: u32) -> u32 {
+        self.line_starts
             .binary_search(&index)
-            .unwrap_or_else(|x| x - 1);
+            .unwrap_or_else(|x| x - 1) as u32
+    }
+
+    /// The span of a line without its line break. Returns `None` if the
+    /// line does not exist.
+    pub fn line_span(&self, line: u32) -> Option<SrcSpan<u32>> {
+        let line = line as usize;
+        let start = *self.line_starts.get(line)?;
+        let end = match self.line_starts.get(line + 1) {
+            Some(next) => next - 1,
+            None => self.input.len() as u32,
+        };
+        Some(SrcSpan { start, end })
+    }
+
+    pub fn src_loc(&self, index: u32) -> SrcLoc {
+        let line = self.line_of(index) as usize;
         let index = index as usize;
         let line_start = self.line_starts[line] as usize;
         let line_index = index - line_start;
         let line_text = &self.input[line_start..];
+        // NOTE: An index in the middle of a character is mapped to the
+        // column after that character.
         let column = line_text
             .char_indices()
             .take_while(|(i, _)| *i < line_index)
-            .count();
+            .map(|(_, char)| self.encoding.width(char))
+            .sum();
         SrcLoc {
             line: line as u32,
-            column: column as u32,
+            column,
         }
     }
+
+    /// Map a source location back to a byte index. Columns beyond the end
+    /// of their line are mapped to the end of the line. Returns `None` if
+    /// the line does not exist or the column is in the middle of a
+    /// character.
+    pub fn index(&self, src_loc: SrcLoc) -> Option<u32> {
+        let line = src_loc.line as usize;
+        let line_start = *self.line_starts.get(line)? as usize;
+        let line_end = self
+            .line_starts
+            .get(line + 1)
+            .map_or(self.input.len(), |start| *start as usize);
+        let line_text = &self.input[line_start..line_end];
+        let line_text = line_text.strip_suffix('\n').unwrap_or(line_text);
+        let mut column = 0;
+        for (i, char) in line_text.char_indices() {
+            if column >= src_loc.column {
+                return (column == src_loc.column).then_some((line_start + i) as u32);
+            }
+            column += self.encoding.width(char);
+        }
+        if column > src_loc.column {
+            return None;
+        }
+        Some((line_start + line_text.len()) as u32)
+    }
+
+    /// Replace the bytes in `span` with `text`. Only the line starts within
+    /// `span` are recomputed, the ones after it are shifted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `span` is not within the text, does not lie on character
+    /// boundaries or the resulting text is too long.
+    pub fn edit(&mut self, span: SrcSpan<u32>, text: &str) {
+        let SrcSpan { start, end } = span;
+        self.input.replace_range(span.into_range(), text);
+        let _: u32 = self.input.len().try_into().expect("input too long");
+        // NOTE: A line starts right after each line break. The lines
+        // starting in `start + 1..=end` lose their line break and the ones
+        // after are moved by the difference in length.
+        let first = self.line_starts.partition_point(|&index| index <= start);
+        let last = self.line_starts.partition_point(|&index| index <= end);
+        let new_end = start + text.len() as u32;
+        for index in &mut self.line_starts[last..] {
+            *index = *index - end + new_end;
+        }
+        self.line_starts
+            .splice(first..last, line_breaks(text, start));
+    }
+}
+
+/// The indices right after the line breaks in `text`, which starts at index
+/// `offset`.
+fn line_breaks(text: &str, offset: u32) -> impl Iterator<Item = u32> + '_ {
+    text.match_indices('\n')
+        .map(move |(index, _)| offset + index as u32 + 1)
 }
 
 impl SrcSpan<u32> {
@@ -180,4 +284,89 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_encodings() {
+        // NOTE: `λ`, `∀` and `𝔸` take 2, 3 and 4 bytes in UTF-8 and 1, 1 and
+        // 2 code units in UTF-16, respectively.
+        let input = "λx\n∀𝔸y\n";
+        let cases = vec![
+            (0, (0, 0), (0, 0), (0, 0)),
+            (2, (0, 2), (0, 1), (0, 1)),
+            (3, (0, 3), (0, 2), (0, 2)),
+            (4, (1, 0), (1, 0), (1, 0)),
+            (7, (1, 3), (1, 1), (1, 1)),
+            (11, (1, 7), (1, 3), (1, 2)),
+            (12, (1, 8), (1, 4), (1, 3)),
+            (13, (2, 0), (2, 0), (2, 0)),
+        ];
+        for (index, utf8, utf16, utf32) in cases {
+            for (encoding, (line, column)) in [
+                (Encoding::Utf8, utf8),
+                (Encoding::Utf16, utf16),
+                (Encoding::Utf32, utf32),
+            ] {
+                let mapper = Mapper::new(input).with_encoding(encoding);
+                let src_loc = SrcLoc { line, column };
+                assert_eq!(
+                    mapper.src_loc(index),
+                    src_loc,
+                    "index: {}, encoding: {:?}",
+                    index,
+                    encoding
+                );
+                assert_eq!(
+                    mapper.index(src_loc),
+                    Some(index),
+                    "src_loc: {:?}, encoding: {:?}",
+                    src_loc,
+                    encoding
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_index_edge_cases() {
+        let input = "a𝔸\r\nb";
+        let utf16 = Mapper::new(input).with_encoding(Encoding::Utf16);
+        // Columns in the middle of a character.
+        assert_eq!(utf16.index(SrcLoc::new(0, 2)), None);
+        let utf8 = Mapper::new(input).with_encoding(Encoding::Utf8);
+        assert_eq!(utf8.index(SrcLoc::new(0, 3)), None);
+        // Columns beyond the end of the line, which includes the `\r`.
+        assert_eq!(utf16.index(SrcLoc::new(0, 4)), Some(6));
+        assert_eq!(utf16.index(SrcLoc::new(0, 9)), Some(6));
+        assert_eq!(utf16.index(SrcLoc::new(1, 9)), Some(8));
+        // Lines beyond the end of the input.
+        assert_eq!(utf16.index(SrcLoc::new(2, 0)), None);
+        assert_eq!(Mapper::new("").index(SrcLoc::new(0, 0)), Some(0));
+    }
+
+    #[test]
+    fn test_edit() {
+        let cases = vec![
+            ("", 0..0, "a\nb"),
+            ("a\nb", 0..3, ""),
+            ("a\nb\nc", 1..2, " "),
+            ("a b c", 1..4, "\n\n"),
+            ("ab\ncd\nef\n", 4..8, "x\ny"),
+            ("ab\ncd\n", 6..6, "\n"),
+            ("λ\nμ∀\n", 3..5, "\nν"),
+            ("a\nb", 3..3, "c\n"),
+        ];
+        for (input, range, text) in cases {
+            let mut mapper = Mapper::new(input).with_encoding(Encoding::Utf16);
+            mapper.edit(SrcSpan::from_range(range.clone()), text);
+            let mut expected = String::from(input);
+            expected.replace_range(range, text);
+            assert_eq!(
+                mapper,
+                Mapper::new(expected).with_encoding(Encoding::Utf16),
+                "input: {:?}, text: {:?}",
+                input,
+                text
+            );
+        }
+    }
 }
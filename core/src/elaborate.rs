@@ -0,0 +1,110 @@
+use std::rc::Rc;
+
+use felix_type_checker::{
+    ast::{self, FromExpr},
+    Checker, Context, Result, TypeError,
+};
+
+use crate::{typed::Expr, Prim};
+
+/// Translate a surface expression into the typed core language. The types
+/// of binders and variables are obtained from `checker`, which must accept
+/// the expression in `ctx`.
+pub fn elaborate(checker: &dyn Checker, ctx: &Context, expr: &ast::Expr) -> Result<Expr> {
+    if let Some(var) = ast::Var::from_expr(expr) {
+        let r#type = checker.lookup(ctx, &var.name)?;
+        Ok(Expr::Var(var.name.as_str().into(), r#type))
+    } else if let Some(abs) = ast::Abs::from_expr(expr) {
+        let param_type = match &abs.binder.annot {
+            Some(r#type) => r#type.clone(),
+            None => checker.decompose_arrow(&checker.infer(ctx, expr)?)?.0,
+        };
+        let ctx = ctx.extend(abs.binder.name.clone(), param_type.clone());
+        let body = elaborate(checker, &ctx, &abs.body)?;
+        Ok(Expr::Abs(
+            abs.binder.name.as_str().into(),
+            param_type,
+            Rc::new(body),
+        ))
+    } else if let Some(app) = ast::App::from_expr(expr) {
+        let fun = elaborate(checker, ctx, &app.fun)?;
+        let arg = elaborate(checker, ctx, &app.arg)?;
+        Ok(Expr::App(Rc::new(fun), Rc::new(arg)))
+    } else if let Some(let_) = ast::Let::from_expr(expr) {
+        let r#type = checker.infer(ctx, &let_.bindee)?;
+        let bindee = elaborate(checker, ctx, &let_.bindee)?;
+        let ctx = ctx.extend(let_.binder.name.clone(), r#type.clone());
+        let body = elaborate(checker, &ctx, &let_.body)?;
+        Ok(Expr::Let(
+            let_.binder.name.as_str().into(),
+            r#type,
+            Rc::new(bindee),
+            Rc::new(body),
+        ))
+    } else if ast::Unit::from_expr(expr).is_some() {
+        Ok(Expr::Prim(Prim::Unit))
+    } else if let Some(broken) = ast::Broken::from_expr(expr) {
+        Err(TypeError::BrokenNode(broken))
+    } else {
+        Err(TypeError::NoInferRule(expr.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use insta::assert_snapshot;
+
+    use felix_type_checker::{ast::*, stlc, Checker, Context, Type, TypeError};
+
+    use super::elaborate;
+    use crate::lint::lint;
+
+    fn twice() -> Expr {
+        let unit_to_unit = Type::Arrow(Type::Unit.into(), Type::Unit.into());
+        let_(
+            binder("twice"),
+            abs(
+                binder_annot("f", unit_to_unit),
+                abs(
+                    binder_annot("u", Type::Unit),
+                    app(var("f"), app(var("f"), var("u"))),
+                ),
+            ),
+            app(
+                app(var("twice"), abs(binder_annot("u", Type::Unit), var("u"))),
+                unit(),
+            ),
+        )
+    }
+
+    #[test]
+    fn elaborate_twice() {
+        let checker = stlc::get();
+        let expr = elaborate(checker, &Context::new(), &twice()).unwrap();
+        assert_snapshot!(expr, @"let twice:((Unit -> Unit) -> (Unit -> Unit)) = λf:(Unit -> Unit). λu:Unit. f (f u) in twice (λu:Unit. u) unit");
+    }
+
+    #[test]
+    fn elaborate_agrees_with_lint() {
+        let checker = stlc::get();
+        let expected = checker.infer(&Context::new(), &twice()).unwrap();
+        let expr = elaborate(checker, &Context::new(), &twice()).unwrap();
+        assert_eq!(lint(&expr).unwrap(), expected);
+    }
+
+    #[test]
+    fn elaborate_unknown_evar() {
+        let checker = stlc::get();
+        let res = elaborate(checker, &Context::new(), &var("x"));
+        assert_matches!(res, Err(TypeError::UnknownEVar(..)));
+    }
+
+    #[test]
+    fn elaborate_broken() {
+        let checker = stlc::get();
+        let expr = let_(binder("x"), broken(), unit());
+        let res = elaborate(checker, &Context::new(), &expr);
+        assert_matches!(res, Err(TypeError::BrokenNode(_)));
+    }
+}
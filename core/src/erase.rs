@@ -0,0 +1,108 @@
+use std::{fmt::Display, rc::Rc};
+
+use felix_type_checker::ast::{self, FromExpr};
+
+use crate::{Expr, Prim};
+
+#[derive(Debug)]
+pub enum EraseError {
+    BrokenNode(Rc<ast::Broken>),
+    UnsupportedNode(ast::Expr),
+}
+
+pub type Result<T> = std::result::Result<T, EraseError>;
+
+impl Display for EraseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BrokenNode(_) => write!(f, "program contains syntax errors"),
+            Self::UnsupportedNode(expr) => write!(f, "unsupported expression: {}", expr),
+        }
+    }
+}
+
+/// Erase all type information from an expression. Type annotations on
+/// binders are dropped. Once the AST has type abstractions and type
+/// applications, they erase to their bodies and heads, respectively.
+///
+/// Expressions with a span are wrapped in a source note.
+pub fn erase(expr: &ast::Expr) -> Result<Expr> {
+    let erased = erase_node(expr)?;
+    Ok(match expr.span() {
+        Some(span) => Expr::Tick(span, Rc::new(erased)),
+        None => erased,
+    })
+}
+
+fn erase_node(expr: &ast::Expr) -> Result<Expr> {
+    if let Some(var) = ast::Var::from_expr(expr) {
+        Ok(Expr::Var(var.name.as_str().into()))
+    } else if let Some(abs) = ast::Abs::from_expr(expr) {
+        Ok(Expr::Abs(
+            abs.binder.name.as_str().into(),
+            Rc::new(erase(&abs.body)?),
+        ))
+    } else if let Some(app) = ast::App::from_expr(expr) {
+        Ok(Expr::App(
+            Rc::new(erase(&app.fun)?),
+            Rc::new(erase(&app.arg)?),
+        ))
+    } else if let Some(let_) = ast::Let::from_expr(expr) {
+        Ok(Expr::Let(
+            let_.binder.name.as_str().into(),
+            Rc::new(erase(&let_.bindee)?),
+            Rc::new(erase(&let_.body)?),
+        ))
+    } else if ast::Unit::from_expr(expr).is_some() {
+        Ok(Expr::Prim(Prim::Unit))
+    } else if let Some(broken) = ast::Broken::from_expr(expr) {
+        Err(EraseError::BrokenNode(broken))
+    } else {
+        Err(EraseError::UnsupportedNode(expr.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use insta::assert_snapshot;
+
+    use felix_type_checker::{ast::*, Type};
+
+    use super::{erase, EraseError};
+
+    #[test]
+    fn erase_annots() {
+        let unit_to_unit = Type::Arrow(Type::Unit.into(), Type::Unit.into());
+        let expr = let_(
+            binder("twice"),
+            abs(
+                binder_annot("f", unit_to_unit),
+                abs(
+                    binder_annot("u", Type::Unit),
+                    app(var("f"), app(var("f"), var("u"))),
+                ),
+            ),
+            app(
+                app(var("twice"), abs(binder_annot("u", Type::Unit), var("u"))),
+                unit(),
+            ),
+        );
+        assert_snapshot!(erase(&expr).unwrap(), @"let twice = λf. λu. f (f u) in twice (λu. u) unit");
+    }
+
+    #[test]
+    fn erase_display_parens() {
+        let expr = app(
+            abs(binder("x"), var("x")),
+            app(var("f"), let_(binder("y"), unit(), var("y"))),
+        );
+        assert_snapshot!(erase(&expr).unwrap(), @"(λx. x) (f (let y = unit in y))");
+    }
+
+    #[test]
+    fn erase_broken() {
+        let expr = abs(binder("x"), app(var("x"), broken()));
+        assert_matches!(erase(&expr), Err(EraseError::BrokenNode(_)));
+    }
+}
@@ -0,0 +1,103 @@
+//! The untyped core language all backends consume: the λ-calculus plus
+//! primitives. Core expressions are obtained from the AST by type erasure.
+//!
+//! The fully annotated variant in `typed` is produced by `elaborate` and
+//! checked by `lint`, which passes can run on their output to catch bugs.
+use std::{fmt::Display, rc::Rc};
+
+use felix_common::SrcSpan;
+
+mod elaborate;
+mod erase;
+mod lint;
+pub mod typed;
+
+pub use elaborate::elaborate;
+pub use erase::{erase, EraseError, Result};
+pub use lint::{lint, lint_in, LintError};
+
+pub type Name = Rc<str>;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Prim {
+    Unit,
+}
+
+#[derive(Clone, Debug)]
+pub enum Expr {
+    Var(Name),
+    Abs(Name, Rc<Expr>),
+    App(Rc<Expr>, Rc<Expr>),
+    Let(Name, Rc<Expr>, Rc<Expr>),
+    Prim(Prim),
+    /// Source note recording that the expression stems from the given span
+    /// of the source code. Source notes have no meaning at runtime but allow
+    /// the interpreter to relate evaluation to the source code.
+    Tick(SrcSpan<u32>, Rc<Expr>),
+}
+
+pub fn var(name: &str) -> Expr {
+    Expr::Var(name.into())
+}
+
+pub fn abs(param: &str, body: Expr) -> Expr {
+    Expr::Abs(param.into(), Rc::new(body))
+}
+
+pub fn app(fun: Expr, arg: Expr) -> Expr {
+    Expr::App(Rc::new(fun), Rc::new(arg))
+}
+
+pub fn let_(name: &str, bindee: Expr, body: Expr) -> Expr {
+    Expr::Let(name.into(), Rc::new(bindee), Rc::new(body))
+}
+
+pub fn unit() -> Expr {
+    Expr::Prim(Prim::Unit)
+}
+
+impl Display for Prim {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unit => write!(f, "unit"),
+        }
+    }
+}
+
+impl Expr {
+    /// Skip all source notes at the root of the expression.
+    pub fn strip_ticks(&self) -> &Expr {
+        let mut expr = self;
+        while let Self::Tick(_, inner) = expr {
+            expr = inner;
+        }
+        expr
+    }
+
+    fn fmt_arg(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.strip_ticks() {
+            Self::Var(_) | Self::Prim(_) => write!(f, "{}", self),
+            _ => write!(f, "({})", self),
+        }
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Var(name) => write!(f, "{}", name),
+            Self::Abs(param, body) => write!(f, "λ{}. {}", param, body),
+            Self::App(fun, arg) => {
+                match fun.strip_ticks() {
+                    Self::App(..) => write!(f, "{}", fun)?,
+                    _ => fun.fmt_arg(f)?,
+                }
+                write!(f, " ")?;
+                arg.fmt_arg(f)
+            }
+            Self::Let(name, bindee, body) => write!(f, "let {} = {} in {}", name, bindee, body),
+            Self::Prim(prim) => write!(f, "{}", prim),
+            Self::Tick(_, expr) => write!(f, "{}", expr),
+        }
+    }
+}
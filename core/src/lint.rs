@@ -0,0 +1,187 @@
+use felix_type_checker::Type;
+
+use crate::{typed::Expr, Name, Prim};
+
+/// Violations of the typing rules of the typed core language. Any of them
+/// indicates a bug in the pass which produced the expression.
+#[derive(Debug)]
+pub enum LintError {
+    UnboundVar(Name),
+    /// The type annotated at a variable occurrence differs from the type of
+    /// its binder.
+    VarTypeMismatch {
+        name: Name,
+        found: Type,
+        expected: Type,
+    },
+    ExpectedArrow {
+        found: Type,
+    },
+    TypeMismatch {
+        found: Type,
+        expected: Type,
+    },
+}
+
+/// Check a closed expression of the typed core language and return its type.
+///
+/// NOTE: This checker is deliberately kept independent of the type checker
+/// for the surface language. It does not perform any inference and every
+/// rule is a direct comparison of annotated types.
+pub fn lint(expr: &Expr) -> Result<Type, LintError> {
+    lint_in(&mut Vec::new(), expr)
+}
+
+/// Check an expression whose free variables have the given types.
+pub fn lint_in(scope: &mut Vec<(Name, Type)>, expr: &Expr) -> Result<Type, LintError> {
+    match expr {
+        Expr::Var(name, r#type) => {
+            let Some((_, expected)) = scope.iter().rev().find(|(bound, _)| bound == name) else {
+                return Err(LintError::UnboundVar(name.clone()));
+            };
+            if r#type != expected {
+                return Err(LintError::VarTypeMismatch {
+                    name: name.clone(),
+                    found: r#type.clone(),
+                    expected: expected.clone(),
+                });
+            }
+            Ok(r#type.clone())
+        }
+        Expr::Abs(param, param_type, body) => {
+            scope.push((param.clone(), param_type.clone()));
+            let body_type = lint_in(scope, body);
+            scope.pop();
+            Ok(Type::Arrow(param_type.clone().into(), body_type?.into()))
+        }
+        Expr::App(fun, arg) => {
+            let fun_type = lint_in(scope, fun)?;
+            let arg_type = lint_in(scope, arg)?;
+            let Type::Arrow(param_type, result_type) = fun_type else {
+                return Err(LintError::ExpectedArrow { found: fun_type });
+            };
+            if arg_type != *param_type {
+                return Err(LintError::TypeMismatch {
+                    found: arg_type,
+                    expected: param_type.as_ref().clone(),
+                });
+            }
+            Ok(result_type.as_ref().clone())
+        }
+        Expr::Let(name, r#type, bindee, body) => {
+            let bindee_type = lint_in(scope, bindee)?;
+            if bindee_type != *r#type {
+                return Err(LintError::TypeMismatch {
+                    found: bindee_type,
+                    expected: r#type.clone(),
+                });
+            }
+            scope.push((name.clone(), r#type.clone()));
+            let body_type = lint_in(scope, body);
+            scope.pop();
+            body_type
+        }
+        Expr::Prim(Prim::Unit) => Ok(Type::Unit),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use assert_matches::assert_matches;
+
+    use felix_type_checker::Type;
+
+    use super::{lint, LintError};
+    use crate::{typed::Expr, Prim};
+
+    fn unit_to_unit() -> Type {
+        Type::Arrow(Type::Unit.into(), Type::Unit.into())
+    }
+
+    fn var(name: &str, r#type: Type) -> Expr {
+        Expr::Var(name.into(), r#type)
+    }
+
+    fn abs(param: &str, r#type: Type, body: Expr) -> Expr {
+        Expr::Abs(param.into(), r#type, Rc::new(body))
+    }
+
+    fn app(fun: Expr, arg: Expr) -> Expr {
+        Expr::App(Rc::new(fun), Rc::new(arg))
+    }
+
+    fn let_(name: &str, r#type: Type, bindee: Expr, body: Expr) -> Expr {
+        Expr::Let(name.into(), r#type, Rc::new(bindee), Rc::new(body))
+    }
+
+    fn unit() -> Expr {
+        Expr::Prim(Prim::Unit)
+    }
+
+    fn id_unit() -> Expr {
+        abs("u", Type::Unit, var("u", Type::Unit))
+    }
+
+    #[test]
+    fn lint_ok() {
+        let expr = let_(
+            "f",
+            unit_to_unit(),
+            id_unit(),
+            app(var("f", unit_to_unit()), unit()),
+        );
+        assert_eq!(lint(&expr).unwrap(), Type::Unit);
+    }
+
+    #[test]
+    fn lint_abs() {
+        assert_eq!(lint(&id_unit()).unwrap(), unit_to_unit());
+    }
+
+    #[test]
+    fn lint_shadowing() {
+        let expr = abs(
+            "x",
+            unit_to_unit(),
+            abs("x", Type::Unit, var("x", Type::Unit)),
+        );
+        assert_eq!(
+            lint(&expr).unwrap(),
+            Type::Arrow(unit_to_unit().into(), unit_to_unit().into())
+        );
+    }
+
+    #[test]
+    fn lint_unbound_var() {
+        let expr = abs("x", Type::Unit, var("y", Type::Unit));
+        assert_matches!(lint(&expr), Err(LintError::UnboundVar(name)) if name.as_ref() == "y");
+    }
+
+    #[test]
+    fn lint_var_type_mismatch() {
+        let expr = abs("x", Type::Unit, var("x", unit_to_unit()));
+        assert_matches!(lint(&expr), Err(LintError::VarTypeMismatch { .. }));
+    }
+
+    #[test]
+    fn lint_expected_arrow() {
+        assert_matches!(
+            lint(&app(unit(), unit())),
+            Err(LintError::ExpectedArrow { found: Type::Unit })
+        );
+    }
+
+    #[test]
+    fn lint_arg_type_mismatch() {
+        let expr = app(id_unit(), id_unit());
+        assert_matches!(lint(&expr), Err(LintError::TypeMismatch { .. }));
+    }
+
+    #[test]
+    fn lint_let_type_mismatch() {
+        let expr = let_("x", unit_to_unit(), unit(), unit());
+        assert_matches!(lint(&expr), Err(LintError::TypeMismatch { .. }));
+    }
+}
@@ -0,0 +1,62 @@
+//! The typed core language. Every binder and every variable occurrence
+//! carries its type, which makes checking it a simple bottom-up pass, see
+//! `lint`.
+use std::{fmt::Display, rc::Rc};
+
+use felix_type_checker::Type;
+
+use crate::{Name, Prim};
+
+#[derive(Clone, Debug)]
+pub enum Expr {
+    Var(Name, Type),
+    Abs(Name, Type, Rc<Expr>),
+    App(Rc<Expr>, Rc<Expr>),
+    Let(Name, Type, Rc<Expr>, Rc<Expr>),
+    Prim(Prim),
+}
+
+impl Expr {
+    /// Drop all types.
+    pub fn erase(&self) -> crate::Expr {
+        match self {
+            Self::Var(name, _) => crate::Expr::Var(name.clone()),
+            Self::Abs(param, _, body) => crate::Expr::Abs(param.clone(), Rc::new(body.erase())),
+            Self::App(fun, arg) => crate::Expr::App(Rc::new(fun.erase()), Rc::new(arg.erase())),
+            Self::Let(name, _, bindee, body) => {
+                crate::Expr::Let(name.clone(), Rc::new(bindee.erase()), Rc::new(body.erase()))
+            }
+            Self::Prim(prim) => crate::Expr::Prim(*prim),
+        }
+    }
+
+    fn fmt_arg(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Var(..) | Self::Prim(_) => write!(f, "{}", self),
+            _ => write!(f, "({})", self),
+        }
+    }
+}
+
+/// Render the expression with the types of all binders. The types of
+/// variable occurrences are omitted.
+impl Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Var(name, _) => write!(f, "{}", name),
+            Self::Abs(param, r#type, body) => write!(f, "λ{}:{}. {}", param, r#type, body),
+            Self::App(fun, arg) => {
+                match fun.as_ref() {
+                    Self::App(..) => write!(f, "{}", fun)?,
+                    _ => fun.fmt_arg(f)?,
+                }
+                write!(f, " ")?;
+                arg.fmt_arg(f)
+            }
+            Self::Let(name, r#type, bindee, body) => {
+                write!(f, "let {}:{} = {} in {}", name, r#type, bindee, body)
+            }
+            Self::Prim(prim) => write!(f, "{}", prim),
+        }
+    }
+}
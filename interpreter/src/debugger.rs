@@ -0,0 +1,358 @@
+//! Step-wise evaluation with breakpoints. The debugger runs an explicit
+//! machine instead of the recursive evaluator, which allows it to pause in
+//! the middle of the evaluation.
+//!
+//! The debugger pauses at source notes around applications and `let`s,
+//! which we call stopping points. Source notes around variables, lambdas
+//! and primitives are not stopping points since their evaluation takes a
+//! single step.
+use std::{collections::HashSet, rc::Rc};
+
+use felix_common::SrcSpan;
+use felix_core::{Expr, Name, Prim};
+
+use crate::{Closure, Env, EvalError, Result, Value};
+
+#[derive(Clone, Debug)]
+pub enum Event {
+    /// Evaluation paused right before the stopping point with the given
+    /// span.
+    Paused(SrcSpan<u32>),
+    Finished(Value),
+    Failed(Rc<EvalError>),
+}
+
+enum State {
+    Eval(Rc<Expr>, Env),
+    Return(Value),
+    Done(Event),
+}
+
+enum Frame {
+    /// The function of an application is being evaluated.
+    AppFun { arg: Rc<Expr>, env: Env },
+    /// The argument of an application is being evaluated.
+    AppArg { fun: Value },
+    LetBindee {
+        name: Name,
+        body: Rc<Expr>,
+        env: Env,
+    },
+    /// An expression with a source note is being evaluated.
+    Tick,
+}
+
+/// How far to run before pausing again.
+#[derive(Clone, Copy)]
+enum Mode {
+    Continue,
+    StepInto,
+    /// Pause at the next stopping point which is not nested more deeply
+    /// than the given number of source notes.
+    StepUntil(usize),
+}
+
+pub struct Debugger {
+    state: State,
+    stack: Vec<Frame>,
+    /// Number of `Frame::Tick`s on the stack.
+    depth: usize,
+    breakpoints: HashSet<SrcSpan<u32>>,
+    /// Whether the machine is paused at a stopping point it must not pause
+    /// at again when resuming.
+    paused: bool,
+}
+
+impl Debugger {
+    pub fn new(expr: &Expr) -> Self {
+        Self {
+            state: State::Eval(Rc::new(expr.clone()), Env::new()),
+            stack: Vec::new(),
+            depth: 0,
+            breakpoints: HashSet::new(),
+            paused: false,
+        }
+    }
+
+    pub fn add_breakpoint(&mut self, span: SrcSpan<u32>) {
+        self.breakpoints.insert(span);
+    }
+
+    pub fn remove_breakpoint(&mut self, span: SrcSpan<u32>) {
+        self.breakpoints.remove(&span);
+    }
+
+    /// Run until a breakpoint is hit or the evaluation terminates.
+    pub fn resume(&mut self) -> Event {
+        self.run(Mode::Continue)
+    }
+
+    /// Run until the next stopping point.
+    pub fn step_into(&mut self) -> Event {
+        self.run(Mode::StepInto)
+    }
+
+    /// Run until the next stopping point which is not part of the
+    /// evaluation of the current one.
+    pub fn step_over(&mut self) -> Event {
+        self.run(Mode::StepUntil(self.depth))
+    }
+
+    /// Run until the next stopping point after the evaluation of the
+    /// expression surrounding the current one has finished.
+    pub fn step_out(&mut self) -> Event {
+        self.run(Mode::StepUntil(self.depth.saturating_sub(1)))
+    }
+
+    /// The span of the stopping point the debugger is paused at.
+    pub fn location(&self) -> Option<SrcSpan<u32>> {
+        match &self.state {
+            State::Eval(expr, _) if self.paused => match expr.as_ref() {
+                Expr::Tick(span, _) => Some(*span),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// The variables in scope at the current location together with their
+    /// values, innermost first. Shadowed variables are omitted.
+    pub fn bindings(&self) -> Vec<(Name, Value)> {
+        match &self.state {
+            State::Eval(_, env) => env.bindings(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn run(&mut self, mode: Mode) -> Event {
+        loop {
+            if let State::Done(event) = &self.state {
+                return event.clone();
+            }
+            if let State::Eval(expr, _) = &self.state {
+                if let Some(span) = stopping_point(expr) {
+                    if !self.paused && self.should_pause(mode, span) {
+                        self.paused = true;
+                        return Event::Paused(span);
+                    }
+                }
+            }
+            self.paused = false;
+            if let Err(error) = self.step() {
+                self.state = State::Done(Event::Failed(Rc::new(error)));
+            }
+        }
+    }
+
+    fn should_pause(&self, mode: Mode, span: SrcSpan<u32>) -> bool {
+        self.breakpoints.contains(&span)
+            || match mode {
+                Mode::Continue => false,
+                Mode::StepInto => true,
+                Mode::StepUntil(depth) => self.depth <= depth,
+            }
+    }
+
+    fn step(&mut self) -> Result<()> {
+        let state = std::mem::replace(&mut self.state, State::Return(Value::Unit));
+        self.state = match state {
+            State::Eval(expr, env) => match expr.as_ref() {
+                Expr::Var(name) => State::Return(
+                    env.lookup(name)
+                        .ok_or_else(|| EvalError::UnknownEVar(name.clone()))?,
+                ),
+                Expr::Abs(param, body) => State::Return(Value::Closure(Rc::new(Closure {
+                    param: param.clone(),
+                    body: body.clone(),
+                    env,
+                }))),
+                Expr::App(fun, arg) => {
+                    self.stack.push(Frame::AppFun {
+                        arg: arg.clone(),
+                        env: env.clone(),
+                    });
+                    State::Eval(fun.clone(), env)
+                }
+                Expr::Let(name, bindee, body) => {
+                    self.stack.push(Frame::LetBindee {
+                        name: name.clone(),
+                        body: body.clone(),
+                        env: env.clone(),
+                    });
+                    State::Eval(bindee.clone(), env)
+                }
+                Expr::Prim(Prim::Unit) => State::Return(Value::Unit),
+                Expr::Tick(_, expr) => {
+                    self.stack.push(Frame::Tick);
+                    self.depth += 1;
+                    State::Eval(expr.clone(), env)
+                }
+            },
+            State::Return(value) => match self.stack.pop() {
+                None => State::Done(Event::Finished(value)),
+                Some(Frame::AppFun { arg, env }) => {
+                    self.stack.push(Frame::AppArg { fun: value });
+                    State::Eval(arg, env)
+                }
+                Some(Frame::AppArg { fun }) => match fun {
+                    Value::Closure(closure) => {
+                        let env = closure.env.extend(closure.param.clone(), value);
+                        State::Eval(closure.body.clone(), env)
+                    }
+                    Value::Unit => return Err(EvalError::NotAFunction(fun)),
+                },
+                Some(Frame::LetBindee { name, body, env }) => {
+                    // NOTE: The body of a `let` is evaluated at the same depth
+                    // as the `let` itself such that stepping over a `let`
+                    // pauses at its body.
+                    if let Some(Frame::Tick) = self.stack.last() {
+                        self.stack.pop();
+                        self.depth -= 1;
+                    }
+                    State::Eval(body, env.extend(name, value))
+                }
+                Some(Frame::Tick) => {
+                    self.depth -= 1;
+                    State::Return(value)
+                }
+            },
+            State::Done(event) => State::Done(event),
+        };
+        Ok(())
+    }
+}
+
+fn stopping_point(expr: &Expr) -> Option<SrcSpan<u32>> {
+    match expr {
+        Expr::Tick(span, inner) => match inner.strip_ticks() {
+            Expr::App(..) | Expr::Let(..) => Some(*span),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use felix_common::{srcloc::Mapper, SrcSpan};
+    use felix_core::Expr;
+    use felix_parser::Parser;
+
+    use super::{Debugger, Event};
+    use crate::{EvalError, Value};
+
+    fn compile(input: &str) -> Expr {
+        let mapper = Mapper::new(input);
+        let result = Parser::new(input, &mapper).run(Parser::program);
+        assert!(result.problems.is_empty());
+        felix_core::erase(&felix_type_checker::lower::lower(&result.syntax)).unwrap()
+    }
+
+    fn span_of(input: &str, text: &str) -> SrcSpan<u32> {
+        let start = input.find(text).expect("text should occur in input");
+        SrcSpan::from_range(start..start + text.len())
+    }
+
+    /// Run the debugger to completion using `step` and record the source
+    /// text of every location it pauses at.
+    fn trace(input: &str, step: fn(&mut Debugger) -> Event) -> Vec<&str> {
+        let mut debugger = Debugger::new(&compile(input));
+        let mut trace = Vec::new();
+        while let Event::Paused(span) = step(&mut debugger) {
+            trace.push(&input[span.into_range()]);
+        }
+        trace
+    }
+
+    const PROGRAM: &str = "let id = λx. x in let u = id unit in id (id u)";
+
+    #[test]
+    fn debug_step_into() {
+        assert_eq!(
+            trace(PROGRAM, Debugger::step_into),
+            vec![
+                PROGRAM,
+                "let u = id unit in id (id u)",
+                "id unit",
+                "id (id u)",
+                "id u",
+            ]
+        );
+    }
+
+    #[test]
+    fn debug_step_over() {
+        assert_eq!(
+            trace(PROGRAM, Debugger::step_over),
+            vec![PROGRAM, "let u = id unit in id (id u)", "id (id u)"]
+        );
+    }
+
+    #[test]
+    fn debug_step_into_function() {
+        let input = "let f = λx. (λy. y) x in f unit";
+        assert_eq!(
+            trace(input, Debugger::step_into),
+            vec![input, "f unit", "(λy. y) x"]
+        );
+    }
+
+    #[test]
+    fn debug_step_out() {
+        let input = "let f = λx. (λy. y) x in let z = f unit in z";
+        let mut debugger = Debugger::new(&compile(input));
+        for _ in 0..4 {
+            debugger.step_into();
+        }
+        assert_eq!(debugger.location(), Some(span_of(input, "(λy. y) x")));
+        assert_matches!(debugger.step_out(), Event::Finished(Value::Unit));
+    }
+
+    #[test]
+    fn debug_breakpoint() {
+        let mut debugger = Debugger::new(&compile(PROGRAM));
+        let span = span_of(PROGRAM, "id (id u)");
+        debugger.add_breakpoint(span);
+        assert_matches!(debugger.resume(), Event::Paused(paused) if paused == span);
+        let bindings: Vec<_> = debugger
+            .bindings()
+            .into_iter()
+            .map(|(name, value)| format!("{} = {}", name, value))
+            .collect();
+        assert_eq!(bindings, vec!["u = unit", "id = <closure λx>"]);
+        assert_matches!(debugger.resume(), Event::Finished(Value::Unit));
+    }
+
+    #[test]
+    fn debug_remove_breakpoint() {
+        let mut debugger = Debugger::new(&compile(PROGRAM));
+        let span = span_of(PROGRAM, "id (id u)");
+        debugger.add_breakpoint(span);
+        debugger.remove_breakpoint(span);
+        assert_matches!(debugger.resume(), Event::Finished(Value::Unit));
+    }
+
+    #[test]
+    fn debug_shadowed_bindings() {
+        let input = "let x = unit in let x = λy. y in x x";
+        let mut debugger = Debugger::new(&compile(input));
+        debugger.add_breakpoint(span_of(input, "x x"));
+        debugger.resume();
+        let names: Vec<_> = debugger
+            .bindings()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, vec!["x".into()]);
+    }
+
+    #[test]
+    fn debug_error() {
+        let mut debugger = Debugger::new(&compile("unit unit"));
+        assert_matches!(debugger.step_into(), Event::Paused(_));
+        assert_matches!(debugger.step_into(), Event::Failed(error) if matches!(*error, EvalError::NotAFunction(_)));
+        assert_matches!(debugger.step_into(), Event::Failed(_));
+    }
+}
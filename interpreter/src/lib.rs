@@ -0,0 +1,216 @@
+use std::{fmt::Display, rc::Rc};
+
+use felix_core::{Expr, Name, Prim};
+
+mod debugger;
+pub mod profiler;
+
+pub use debugger::{Debugger, Event};
+
+#[derive(Clone, Debug)]
+pub enum Value {
+    Unit,
+    Closure(Rc<Closure>),
+}
+
+#[derive(Debug)]
+pub struct Closure {
+    pub param: Name,
+    pub body: Rc<Expr>,
+    pub env: Env,
+}
+
+#[derive(Debug)]
+enum EnvData {
+    Empty,
+    Binding {
+        ident: Name,
+        value: Value,
+        next: Env,
+    },
+}
+
+/// Environment mapping identifiers to values. Extending an environment does
+/// not affect the environment it was extended from.
+#[derive(Clone, Debug)]
+pub struct Env(Rc<EnvData>);
+
+impl Default for Env {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Env {
+    pub fn new() -> Self {
+        Self(Rc::new(EnvData::Empty))
+    }
+
+    pub fn lookup(&self, ident: &Name) -> Option<Value> {
+        let mut env = self;
+        loop {
+            match env.0.as_ref() {
+                EnvData::Empty => return None,
+                EnvData::Binding {
+                    ident: bound,
+                    value,
+                    next,
+                } => {
+                    if ident == bound {
+                        return Some(value.clone());
+                    }
+                    env = next;
+                }
+            }
+        }
+    }
+
+    /// All bindings which are not shadowed, innermost first.
+    pub fn bindings(&self) -> Vec<(Name, Value)> {
+        let mut bindings: Vec<(Name, Value)> = Vec::new();
+        let mut env = self;
+        while let EnvData::Binding { ident, value, next } = env.0.as_ref() {
+            if !bindings.iter().any(|(bound, _)| bound == ident) {
+                bindings.push((ident.clone(), value.clone()));
+            }
+            env = next;
+        }
+        bindings
+    }
+
+    pub fn extend(&self, ident: Name, value: Value) -> Self {
+        Self(Rc::new(EnvData::Binding {
+            ident,
+            value,
+            next: self.clone(),
+        }))
+    }
+}
+
+#[derive(Debug)]
+pub enum EvalError {
+    UnknownEVar(Name),
+    NotAFunction(Value),
+}
+
+pub type Result<T> = std::result::Result<T, EvalError>;
+
+impl Display for EvalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownEVar(name) => write!(f, "unknown variable: {}", name),
+            Self::NotAFunction(value) => write!(f, "not a function: {}", value),
+        }
+    }
+}
+
+/// Evaluate a closed expression using call-by-value.
+pub fn eval(expr: &Expr) -> Result<Value> {
+    eval_in(&Env::new(), expr)
+}
+
+pub fn eval_in(env: &Env, expr: &Expr) -> Result<Value> {
+    match expr {
+        Expr::Var(name) => env
+            .lookup(name)
+            .ok_or_else(|| EvalError::UnknownEVar(name.clone())),
+        Expr::Abs(param, body) => Ok(Value::Closure(Rc::new(Closure {
+            param: param.clone(),
+            body: body.clone(),
+            env: env.clone(),
+        }))),
+        Expr::App(fun, arg) => {
+            let fun = eval_in(env, fun)?;
+            let arg = eval_in(env, arg)?;
+            apply(fun, arg)
+        }
+        Expr::Let(name, bindee, body) => {
+            let bindee = eval_in(env, bindee)?;
+            eval_in(&env.extend(name.clone(), bindee), body)
+        }
+        Expr::Prim(Prim::Unit) => Ok(Value::Unit),
+        Expr::Tick(_, expr) => eval_in(env, expr),
+    }
+}
+
+pub fn apply(fun: Value, arg: Value) -> Result<Value> {
+    match fun {
+        Value::Closure(closure) => {
+            let env = closure.env.extend(closure.param.clone(), arg);
+            eval_in(&env, &closure.body)
+        }
+        Value::Unit => Err(EvalError::NotAFunction(fun)),
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unit => write!(f, "unit"),
+            Self::Closure(closure) => write!(f, "<closure λ{}>", closure.param),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use felix_core::*;
+
+    use super::{eval, EvalError, Value};
+
+    #[test]
+    fn eval_unit() {
+        assert_matches!(eval(&unit()), Ok(Value::Unit));
+    }
+
+    #[test]
+    fn eval_abs() {
+        assert_matches!(eval(&abs("x", var("y"))), Ok(Value::Closure(_)));
+    }
+
+    #[test]
+    fn eval_app() {
+        let expr = app(abs("x", var("x")), unit());
+        assert_matches!(eval(&expr), Ok(Value::Unit));
+    }
+
+    #[test]
+    fn eval_app_not_a_function() {
+        let expr = app(unit(), unit());
+        assert_matches!(eval(&expr), Err(EvalError::NotAFunction(Value::Unit)));
+    }
+
+    #[test]
+    fn eval_let() {
+        let expr = let_("x", unit(), var("x"));
+        assert_matches!(eval(&expr), Ok(Value::Unit));
+    }
+
+    #[test]
+    fn eval_let_shadowing() {
+        let expr = let_("x", abs("y", var("y")), let_("x", unit(), var("x")));
+        assert_matches!(eval(&expr), Ok(Value::Unit));
+    }
+
+    #[test]
+    fn eval_static_scoping() {
+        // `f` must see the `x` from its definition, not the one at its call.
+        let expr = let_(
+            "x",
+            unit(),
+            let_(
+                "f",
+                abs("y", var("x")),
+                let_("x", abs("z", var("z")), app(var("f"), var("x"))),
+            ),
+        );
+        assert_matches!(eval(&expr), Ok(Value::Unit));
+    }
+
+    #[test]
+    fn eval_unknown_evar() {
+        assert_matches!(eval(&var("x")), Err(EvalError::UnknownEVar(_)));
+    }
+}
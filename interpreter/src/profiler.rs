@@ -0,0 +1,382 @@
+//! Instrumented evaluation which counts the work done on behalf of each
+//! source note. Every count is attributed to the innermost source note on
+//! the evaluation stack. Since the bodies of functions are evaluated on top
+//! of the application calling them, the stack of source notes doubles as a
+//! call stack, which makes the profile suitable for flame graphs.
+use std::{collections::HashMap, fmt::Write, rc::Rc};
+
+use felix_common::{srcloc::Mapper, SrcSpan};
+use felix_core::{Expr, Prim};
+
+use crate::{Closure, Env, EvalError, Result, Value};
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Counts {
+    /// Number of applications of closures to arguments.
+    pub reductions: u64,
+    /// Number of closures and environment entries allocated.
+    pub allocations: u64,
+    /// Number of variables looked up in the environment.
+    pub lookups: u64,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Counter {
+    Reductions,
+    Allocations,
+    Lookups,
+}
+
+impl Counts {
+    pub fn get(&self, counter: Counter) -> u64 {
+        match counter {
+            Counter::Reductions => self.reductions,
+            Counter::Allocations => self.allocations,
+            Counter::Lookups => self.lookups,
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.reductions + self.allocations + self.lookups
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.reductions += other.reductions;
+        self.allocations += other.allocations;
+        self.lookups += other.lookups;
+    }
+}
+
+/// A node in the tree of all stacks of source notes seen during evaluation.
+struct Frame {
+    span: Option<SrcSpan<u32>>,
+    parent: usize,
+    children: HashMap<SrcSpan<u32>, usize>,
+    counts: Counts,
+}
+
+/// The counts collected while evaluating an expression.
+pub struct Profile {
+    /// The root of the tree is at index 0 and has no span.
+    frames: Vec<Frame>,
+}
+
+/// Evaluate a closed expression using call-by-value and profile the
+/// evaluation. The profile covers the evaluation up to the point where it
+/// failed, if it does.
+pub fn profile(expr: &Expr) -> (Result<Value>, Profile) {
+    let mut profiler = Profiler {
+        profile: Profile {
+            frames: vec![Frame {
+                span: None,
+                parent: 0,
+                children: HashMap::new(),
+                counts: Counts::default(),
+            }],
+        },
+        current: 0,
+    };
+    let result = profiler.eval_in(&Env::new(), expr);
+    (result, profiler.profile)
+}
+
+struct Profiler {
+    profile: Profile,
+    /// Index of the frame for the current stack of source notes.
+    current: usize,
+}
+
+impl Profiler {
+    fn counts(&mut self) -> &mut Counts {
+        &mut self.profile.frames[self.current].counts
+    }
+
+    fn enter(&mut self, span: SrcSpan<u32>) {
+        let frames = &mut self.profile.frames;
+        self.current = match frames[self.current].children.get(&span) {
+            Some(child) => *child,
+            None => {
+                let child = frames.len();
+                frames.push(Frame {
+                    span: Some(span),
+                    parent: self.current,
+                    children: HashMap::new(),
+                    counts: Counts::default(),
+                });
+                frames[self.current].children.insert(span, child);
+                child
+            }
+        };
+    }
+
+    fn eval_in(&mut self, env: &Env, expr: &Expr) -> Result<Value> {
+        match expr {
+            Expr::Var(name) => {
+                self.counts().lookups += 1;
+                env.lookup(name)
+                    .ok_or_else(|| EvalError::UnknownEVar(name.clone()))
+            }
+            Expr::Abs(param, body) => {
+                self.counts().allocations += 1;
+                Ok(Value::Closure(Rc::new(Closure {
+                    param: param.clone(),
+                    body: body.clone(),
+                    env: env.clone(),
+                })))
+            }
+            Expr::App(fun, arg) => {
+                let fun = self.eval_in(env, fun)?;
+                let arg = self.eval_in(env, arg)?;
+                match fun {
+                    Value::Closure(closure) => {
+                        let counts = self.counts();
+                        counts.reductions += 1;
+                        counts.allocations += 1;
+                        let env = closure.env.extend(closure.param.clone(), arg);
+                        self.eval_in(&env, &closure.body)
+                    }
+                    Value::Unit => Err(EvalError::NotAFunction(fun)),
+                }
+            }
+            Expr::Let(name, bindee, body) => {
+                let bindee = self.eval_in(env, bindee)?;
+                self.counts().allocations += 1;
+                self.eval_in(&env.extend(name.clone(), bindee), body)
+            }
+            Expr::Prim(Prim::Unit) => Ok(Value::Unit),
+            Expr::Tick(span, expr) => {
+                let caller = self.current;
+                self.enter(*span);
+                let result = self.eval_in(env, expr);
+                self.current = caller;
+                result
+            }
+        }
+    }
+}
+
+impl Profile {
+    /// The counts of all source notes, summed over all stacks the note
+    /// occurs at the top of. The most expensive source notes come first and
+    /// source notes without any counts are omitted.
+    pub fn spans(&self) -> Vec<(SrcSpan<u32>, Counts)> {
+        let mut spans: HashMap<SrcSpan<u32>, Counts> = HashMap::new();
+        for frame in &self.frames {
+            if let Some(span) = frame.span {
+                spans.entry(span).or_default().add(&frame.counts);
+            }
+        }
+        let mut spans: Vec<_> = spans
+            .into_iter()
+            .filter(|(_, counts)| counts.total() > 0)
+            .collect();
+        spans.sort_by_key(|(span, counts)| (std::cmp::Reverse(counts.total()), *span));
+        spans
+    }
+
+    /// The counts which are not attributed to any source note.
+    pub fn unattributed(&self) -> Counts {
+        self.frames[0].counts
+    }
+
+    /// Render the counts per source note as a table sorted by cost.
+    pub fn table(&self, mapper: &Mapper) -> String {
+        let mut rows = vec![[
+            String::from("span"),
+            String::from("reductions"),
+            String::from("allocations"),
+            String::from("lookups"),
+        ]];
+        let spans = self
+            .spans()
+            .into_iter()
+            .map(|(span, counts)| (label(mapper, span), counts));
+        let unattributed = self.unattributed();
+        let unattributed = (unattributed != Counts::default())
+            .then(|| (String::from("<unattributed>"), unattributed));
+        for (label, counts) in spans.chain(unattributed) {
+            rows.push([
+                label,
+                counts.reductions.to_string(),
+                counts.allocations.to_string(),
+                counts.lookups.to_string(),
+            ]);
+        }
+        let width = rows
+            .iter()
+            .map(|row| row[0].chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut table = String::new();
+        for [label, reductions, allocations, lookups] in rows {
+            writeln!(
+                table,
+                "{:<width$}  {:>10}  {:>11}  {:>7}",
+                label, reductions, allocations, lookups
+            )
+            .unwrap();
+        }
+        table
+    }
+
+    /// Render the given counter in the folded stack format understood by
+    /// flame graph tools: one line per stack of source notes, with the
+    /// frames separated by `;` and followed by the count.
+    pub fn folded(&self, mapper: &Mapper, counter: Counter) -> String {
+        let mut lines = Vec::new();
+        for (index, frame) in self.frames.iter().enumerate() {
+            let count = frame.counts.get(counter);
+            if count == 0 {
+                continue;
+            }
+            let mut stack = Vec::new();
+            let mut index = index;
+            while let Some(span) = self.frames[index].span {
+                stack.push(label(mapper, span));
+                index = self.frames[index].parent;
+            }
+            if stack.is_empty() {
+                stack.push(String::from("<unattributed>"));
+            }
+            stack.reverse();
+            lines.push(format!("{} {}\n", stack.join(";"), count));
+        }
+        lines.sort();
+        lines.concat()
+    }
+}
+
+/// Label a span by its one-based start and end positions.
+fn label(mapper: &Mapper, span: SrcSpan<u32>) -> String {
+    let start = mapper.src_loc(span.start);
+    let end = mapper.src_loc(span.end);
+    format!(
+        "{}:{}-{}:{}",
+        start.line + 1,
+        start.column + 1,
+        end.line + 1,
+        end.column + 1
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use felix_common::srcloc::Mapper;
+    use felix_core::Expr;
+    use felix_parser::Parser;
+
+    use super::{profile, Counter, Counts};
+    use crate::{EvalError, Value};
+
+    fn compile(input: &str) -> Expr {
+        let mapper = Mapper::new(input);
+        let result = Parser::new(input, &mapper).run(Parser::program);
+        assert!(result.problems.is_empty());
+        felix_core::erase(&felix_type_checker::lower::lower(&result.syntax)).unwrap()
+    }
+
+    const PROGRAM: &str = "let id = λx. x in\nlet u = id unit in\nid (id u)";
+
+    #[test]
+    fn profile_result() {
+        let (result, _) = profile(&compile(PROGRAM));
+        assert_matches!(result, Ok(Value::Unit));
+    }
+
+    #[test]
+    fn profile_spans() {
+        let (_, profile) = profile(&compile(PROGRAM));
+        let spans: Vec<_> = profile
+            .spans()
+            .into_iter()
+            .map(|(span, counts)| (&PROGRAM[span.into_range()], counts))
+            .collect();
+        let counts = |reductions, allocations, lookups| Counts {
+            reductions,
+            allocations,
+            lookups,
+        };
+        assert_eq!(
+            spans,
+            vec![
+                ("x", counts(0, 0, 3)),
+                ("id unit", counts(1, 1, 0)),
+                ("id (id u)", counts(1, 1, 0)),
+                ("id u", counts(1, 1, 0)),
+                (PROGRAM, counts(0, 1, 0)),
+                ("λx. x", counts(0, 1, 0)),
+                ("let u = id unit in\nid (id u)", counts(0, 1, 0)),
+                ("id", counts(0, 0, 1)),
+                ("id", counts(0, 0, 1)),
+                ("id", counts(0, 0, 1)),
+                ("u", counts(0, 0, 1)),
+            ]
+        );
+        assert_eq!(profile.unattributed(), Counts::default());
+    }
+
+    #[test]
+    fn profile_unattributed() {
+        let expr = felix_core::app(
+            felix_core::abs("x", felix_core::var("x")),
+            felix_core::unit(),
+        );
+        let (_, profile) = profile(&expr);
+        assert!(profile.spans().is_empty());
+        let expected = Counts {
+            reductions: 1,
+            allocations: 2,
+            lookups: 1,
+        };
+        assert_eq!(profile.unattributed(), expected);
+    }
+
+    #[test]
+    fn profile_table() {
+        let input = "let f = λx. x x in f (λy. y)";
+        let (_, profile) = profile(&compile(input));
+        assert_eq!(
+            profile.table(&Mapper::new(input)),
+            "\
+span       reductions  allocations  lookups
+1:13-1:16           1            1        0
+1:20-1:29           1            1        0
+1:1-1:29            0            1        0
+1:9-1:16            0            1        0
+1:13-1:14           0            0        1
+1:15-1:16           0            0        1
+1:20-1:21           0            0        1
+1:23-1:28           0            1        0
+1:27-1:28           0            0        1
+"
+        );
+    }
+
+    #[test]
+    fn profile_folded() {
+        let input = "let f = λx. x x in f (λy. y)";
+        let (_, profile) = profile(&compile(input));
+        let mapper = Mapper::new(input);
+        assert_eq!(
+            profile.folded(&mapper, Counter::Reductions),
+            "\
+1:1-1:29;1:20-1:29 1
+1:1-1:29;1:20-1:29;1:13-1:16 1
+"
+        );
+    }
+
+    #[test]
+    fn profile_failure() {
+        let (result, profile) = profile(&compile("let x = unit in x x"));
+        assert_matches!(result, Err(EvalError::NotAFunction(Value::Unit)));
+        let lookups: u64 = profile
+            .spans()
+            .iter()
+            .map(|(_, counts)| counts.lookups)
+            .sum();
+        assert_eq!(lookups, 2);
+    }
+}
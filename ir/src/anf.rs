@@ -0,0 +1,212 @@
+//! Administrative normal form: every argument of an application is an atom
+//! and every intermediate result is bound to a name.
+use std::{fmt::Display, rc::Rc};
+
+use felix_type_checker::ast::{self, Binder, FromExpr, Ident};
+
+use crate::{fresh, Result, TransformError};
+
+#[derive(Clone, Debug)]
+pub enum Atom {
+    Var(Ident),
+    Unit,
+    Abs(Binder, Rc<Expr>),
+}
+
+#[derive(Clone, Debug)]
+pub enum Comp {
+    Atom(Atom),
+    App(Atom, Atom),
+}
+
+#[derive(Clone, Debug)]
+pub enum Expr {
+    Let(Binder, Comp, Rc<Expr>),
+    Comp(Comp),
+}
+
+/// Convert an expression into administrative normal form. Intermediate
+/// results are bound to fresh names of the form `%n`.
+pub fn convert(expr: &ast::Expr) -> Result<Expr> {
+    let mut fresh = fresh::Fresh::default();
+    let expr = fresh::uniquify(&mut fresh, expr)?;
+    Converter { fresh }.term(&expr)
+}
+
+type CompCont = Box<dyn FnOnce(&mut Converter, Comp) -> Result<Expr>>;
+type AtomCont = Box<dyn FnOnce(&mut Converter, Atom) -> Result<Expr>>;
+
+struct Converter {
+    fresh: fresh::Fresh,
+}
+
+impl Converter {
+    fn term(&mut self, expr: &ast::Expr) -> Result<Expr> {
+        self.comp(expr, Box::new(|_, comp| Ok(Expr::Comp(comp))))
+    }
+
+    fn comp(&mut self, expr: &ast::Expr, k: CompCont) -> Result<Expr> {
+        if let Some(var) = ast::Var::from_expr(expr) {
+            k(self, Comp::Atom(Atom::Var(var.name.clone())))
+        } else if let Some(abs) = ast::Abs::from_expr(expr) {
+            let body = self.term(&abs.body)?;
+            k(
+                self,
+                Comp::Atom(Atom::Abs(abs.binder.clone(), Rc::new(body))),
+            )
+        } else if let Some(app) = ast::App::from_expr(expr) {
+            let arg = app.arg.clone();
+            self.atom(
+                &app.fun,
+                Box::new(move |this, fun| {
+                    this.atom(
+                        &arg,
+                        Box::new(move |this, arg| k(this, Comp::App(fun, arg))),
+                    )
+                }),
+            )
+        } else if let Some(let_) = ast::Let::from_expr(expr) {
+            let binder = let_.binder.clone();
+            let body = let_.body.clone();
+            self.comp(
+                &let_.bindee,
+                Box::new(move |this, bindee| {
+                    let body = this.comp(&body, k)?;
+                    Ok(Expr::Let(binder, bindee, Rc::new(body)))
+                }),
+            )
+        } else if ast::Unit::from_expr(expr).is_some() {
+            k(self, Comp::Atom(Atom::Unit))
+        } else {
+            Err(TransformError::from_expr(expr))
+        }
+    }
+
+    fn atom(&mut self, expr: &ast::Expr, k: AtomCont) -> Result<Expr> {
+        self.comp(
+            expr,
+            Box::new(|this, comp| match comp {
+                Comp::Atom(atom) => k(this, atom),
+                comp => {
+                    let temp = this.fresh.temp();
+                    let body = k(this, Atom::Var(temp.clone()))?;
+                    let binder = Binder {
+                        name: temp,
+                        annot: None,
+                    };
+                    Ok(Expr::Let(binder, comp, Rc::new(body)))
+                }
+            }),
+        )
+    }
+}
+
+impl Atom {
+    pub fn to_expr(&self) -> ast::Expr {
+        match self {
+            Self::Var(name) => ast::var(name.as_str()),
+            Self::Unit => ast::unit(),
+            Self::Abs(binder, body) => ast::abs(binder.clone(), body.to_expr()),
+        }
+    }
+}
+
+impl Comp {
+    pub fn to_expr(&self) -> ast::Expr {
+        match self {
+            Self::Atom(atom) => atom.to_expr(),
+            Self::App(fun, arg) => ast::app(fun.to_expr(), arg.to_expr()),
+        }
+    }
+}
+
+impl Expr {
+    /// Read the expression back into the surface AST.
+    pub fn to_expr(&self) -> ast::Expr {
+        match self {
+            Self::Let(binder, bindee, body) => {
+                ast::let_(binder.clone(), bindee.to_expr(), body.to_expr())
+            }
+            Self::Comp(comp) => comp.to_expr(),
+        }
+    }
+}
+
+// The pretty printer puts every `let` on a line of its own. Bodies of lambdas
+// which contain a `let` start on a new line and are indented.
+
+const INDENT: &str = "    ";
+
+impl Atom {
+    fn render(&self, indent: usize, out: &mut String) {
+        match self {
+            Self::Var(name) => out.push_str(name.as_str()),
+            Self::Unit => out.push_str("unit"),
+            Self::Abs(binder, body) => {
+                out.push_str(&format!("λ{}.", binder));
+                match body.as_ref() {
+                    Expr::Comp(comp) => {
+                        out.push(' ');
+                        comp.render(indent, out);
+                    }
+                    Expr::Let(..) => {
+                        out.push('\n');
+                        out.push_str(&INDENT.repeat(indent + 1));
+                        body.render(indent + 1, out);
+                    }
+                }
+            }
+        }
+    }
+
+    fn render_arg(&self, indent: usize, out: &mut String) {
+        if let Self::Abs(..) = self {
+            out.push('(');
+            self.render(indent, out);
+            out.push(')');
+        } else {
+            self.render(indent, out);
+        }
+    }
+}
+
+impl Comp {
+    fn render(&self, indent: usize, out: &mut String) {
+        match self {
+            Self::Atom(atom) => atom.render(indent, out),
+            Self::App(fun, arg) => {
+                fun.render_arg(indent, out);
+                out.push(' ');
+                arg.render_arg(indent, out);
+            }
+        }
+    }
+}
+
+impl Expr {
+    fn render(&self, indent: usize, out: &mut String) {
+        match self {
+            Self::Let(binder, bindee, body) => {
+                let mut rendered = String::new();
+                bindee.render(indent, &mut rendered);
+                let prefix = INDENT.repeat(indent);
+                out.push_str(&format!("let {} = {}", binder, rendered));
+                if rendered.contains('\n') {
+                    out.push_str(&format!("\n{}in\n{}", prefix, prefix));
+                } else {
+                    out.push_str(&format!(" in\n{}", prefix));
+                }
+                body.render(indent, out);
+            }
+            Self::Comp(comp) => comp.render(indent, out),
+        }
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut out = String::new();
+        self.render(0, &mut out);
+        write!(f, "{}", out)
+    }
+}
@@ -0,0 +1,48 @@
+//! Church encodings: values of data types are represented by functions which
+//! perform case analysis on them.
+use felix_type_checker::ast::{self, Binder, FromExpr};
+
+use crate::{Result, TransformError};
+
+/// Replace all data by its Church encoding. The only data type the AST has
+/// at the moment is `Unit`, whose only value is encoded as `λu. u`.
+///
+/// Type annotations are dropped since the Church encoding of a type cannot be
+/// expressed without polymorphism. The result is a term of the pure untyped
+/// λ-calculus.
+pub fn convert(expr: &ast::Expr) -> Result<ast::Expr> {
+    if let Some(var) = ast::Var::from_expr(expr) {
+        Ok(ast::var(var.name.as_str()))
+    } else if let Some(abs) = ast::Abs::from_expr(expr) {
+        Ok(ast::abs(erase(&abs.binder), convert(&abs.body)?))
+    } else if let Some(app) = ast::App::from_expr(expr) {
+        Ok(ast::app(convert(&app.fun)?, convert(&app.arg)?))
+    } else if let Some(let_) = ast::Let::from_expr(expr) {
+        Ok(ast::let_(
+            erase(&let_.binder),
+            convert(&let_.bindee)?,
+            convert(&let_.body)?,
+        ))
+    } else if ast::Unit::from_expr(expr).is_some() {
+        Ok(unit())
+    } else {
+        Err(TransformError::from_expr(expr))
+    }
+}
+
+/// The Church encoding of `unit`.
+pub fn unit() -> ast::Expr {
+    ast::abs(ast::binder("u"), ast::var("u"))
+}
+
+/// Turn the Church encoding of a unit value back into `unit`.
+pub fn decode_unit(expr: ast::Expr) -> ast::Expr {
+    ast::app(expr, ast::unit())
+}
+
+fn erase(binder: &Binder) -> Binder {
+    Binder {
+        name: binder.name.clone(),
+        annot: None,
+    }
+}
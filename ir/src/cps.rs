@@ -0,0 +1,181 @@
+//! Continuation-passing style: functions take their continuation as an
+//! additional argument and never return.
+use std::{fmt::Display, rc::Rc};
+
+use felix_type_checker::ast::{self, Binder, FromExpr, Ident};
+
+use crate::{fresh, Result, TransformError};
+
+#[derive(Clone, Debug)]
+pub enum Value {
+    Var(Ident),
+    Unit,
+    /// A function taking a parameter and a continuation.
+    Abs(Binder, Ident, Rc<Term>),
+}
+
+#[derive(Clone, Debug)]
+pub enum Cont {
+    Var(Ident),
+    Abs(Binder, Rc<Term>),
+}
+
+#[derive(Clone, Debug)]
+pub enum Term {
+    /// Call a function with an argument and a continuation.
+    App(Value, Value, Cont),
+    /// Pass a value to a continuation.
+    Ret(Cont, Value),
+    /// Pass a value to the top-level continuation.
+    Halt(Value),
+}
+
+/// Convert an expression into continuation-passing style using a one-pass
+/// transformation which does not introduce administrative redexes.
+/// Continuation variables have names of the form `k%n`, the parameters of
+/// continuations introduced by the transformation have names of the form
+/// `%n`.
+pub fn convert(expr: &ast::Expr) -> Result<Term> {
+    let mut fresh = fresh::Fresh::default();
+    let expr = fresh::uniquify(&mut fresh, expr)?;
+    Converter { fresh }.term(
+        &expr,
+        MetaCont::Static(Box::new(|_, value| Ok(Term::Halt(value)))),
+    )
+}
+
+type StaticCont = Box<dyn FnOnce(&mut Converter, Value) -> Result<Term>>;
+
+/// Continuation of the transformation. Static continuations are functions at
+/// transformation time and are only turned into a `Cont` when necessary.
+enum MetaCont {
+    Static(StaticCont),
+    Dynamic(Cont),
+}
+
+struct Converter {
+    fresh: fresh::Fresh,
+}
+
+impl Converter {
+    fn ret(&mut self, k: MetaCont, value: Value) -> Result<Term> {
+        match k {
+            MetaCont::Static(k) => k(self, value),
+            MetaCont::Dynamic(k) => Ok(Term::Ret(k, value)),
+        }
+    }
+
+    fn reify(&mut self, k: MetaCont) -> Result<Cont> {
+        match k {
+            MetaCont::Static(k) => {
+                let param = self.fresh.temp();
+                let body = k(self, Value::Var(param.clone()))?;
+                let binder = Binder {
+                    name: param,
+                    annot: None,
+                };
+                Ok(Cont::Abs(binder, Rc::new(body)))
+            }
+            MetaCont::Dynamic(k) => Ok(k),
+        }
+    }
+
+    fn term(&mut self, expr: &ast::Expr, k: MetaCont) -> Result<Term> {
+        if let Some(var) = ast::Var::from_expr(expr) {
+            self.ret(k, Value::Var(var.name.clone()))
+        } else if let Some(abs) = ast::Abs::from_expr(expr) {
+            let cont = self.fresh.next("k");
+            let body = self.term(&abs.body, MetaCont::Dynamic(Cont::Var(cont.clone())))?;
+            self.ret(k, Value::Abs(abs.binder.clone(), cont, Rc::new(body)))
+        } else if let Some(app) = ast::App::from_expr(expr) {
+            let arg = app.arg.clone();
+            self.term(
+                &app.fun,
+                MetaCont::Static(Box::new(move |this, fun| {
+                    this.term(
+                        &arg,
+                        MetaCont::Static(Box::new(move |this, arg| {
+                            let k = this.reify(k)?;
+                            Ok(Term::App(fun, arg, k))
+                        })),
+                    )
+                })),
+            )
+        } else if let Some(let_) = ast::Let::from_expr(expr) {
+            let body = self.term(&let_.body, k)?;
+            let k = Cont::Abs(let_.binder.clone(), Rc::new(body));
+            self.term(&let_.bindee, MetaCont::Dynamic(k))
+        } else if ast::Unit::from_expr(expr).is_some() {
+            self.ret(k, Value::Unit)
+        } else {
+            Err(TransformError::from_expr(expr))
+        }
+    }
+}
+
+impl Value {
+    pub fn to_expr(&self) -> ast::Expr {
+        match self {
+            Self::Var(name) => ast::var(name.as_str()),
+            Self::Unit => ast::unit(),
+            Self::Abs(binder, cont, body) => ast::abs(
+                binder.clone(),
+                ast::abs(ast::binder(cont.as_str()), body.to_expr()),
+            ),
+        }
+    }
+}
+
+impl Cont {
+    pub fn to_expr(&self) -> ast::Expr {
+        match self {
+            Self::Var(name) => ast::var(name.as_str()),
+            Self::Abs(binder, body) => ast::abs(binder.clone(), body.to_expr()),
+        }
+    }
+}
+
+impl Term {
+    /// Read the term back into the surface AST. The top-level continuation
+    /// is the identity.
+    pub fn to_expr(&self) -> ast::Expr {
+        match self {
+            Self::App(fun, arg, cont) => {
+                ast::app(ast::app(fun.to_expr(), arg.to_expr()), cont.to_expr())
+            }
+            Self::Ret(cont, value) => ast::app(cont.to_expr(), value.to_expr()),
+            Self::Halt(value) => value.to_expr(),
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Var(name) => write!(f, "{}", name.as_str()),
+            Self::Unit => write!(f, "unit"),
+            Self::Abs(binder, cont, body) => {
+                write!(f, "(λ{} {}. {})", binder, cont.as_str(), body)
+            }
+        }
+    }
+}
+
+impl Display for Cont {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Var(name) => write!(f, "{}", name.as_str()),
+            Self::Abs(binder, body) => write!(f, "(λ{}. {})", binder, body),
+        }
+    }
+}
+
+impl Display for Term {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::App(fun, arg, cont) => write!(f, "{} {} {}", fun, arg, cont),
+            Self::Ret(cont, value) => write!(f, "{} {}", cont, value),
+            Self::Halt(value) => write!(f, "halt {}", value),
+        }
+    }
+}
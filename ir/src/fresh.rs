@@ -0,0 +1,113 @@
+use std::collections::HashSet;
+
+use felix_type_checker::ast::{self, Binder, Expr, FromExpr, Ident};
+
+use crate::{Result, TransformError};
+
+/// Supply of identifiers which cannot clash with identifiers from the source
+/// since they contain a `%`.
+#[derive(Default)]
+pub(crate) struct Fresh {
+    counter: u32,
+}
+
+impl Fresh {
+    pub(crate) fn next(&mut self, base: &str) -> Ident {
+        self.counter += 1;
+        ast::ident(&format!("{}%{}", base, self.counter))
+    }
+
+    pub(crate) fn temp(&mut self) -> Ident {
+        self.next("")
+    }
+}
+
+/// Rename binders such that all binders are distinct from each other and
+/// from the free variables. Transformations which move code across binders,
+/// like ANF and CPS conversion, rely on this to avoid variable capture.
+pub(crate) fn uniquify(fresh: &mut Fresh, expr: &Expr) -> Result<Expr> {
+    let mut taken = HashSet::new();
+    free_vars(expr, &mut Vec::new(), &mut taken)?;
+    let mut uniquify = Uniquify {
+        fresh,
+        taken,
+        scope: Vec::new(),
+    };
+    uniquify.expr(expr)
+}
+
+fn free_vars(expr: &Expr, bound: &mut Vec<Ident>, free: &mut HashSet<String>) -> Result<()> {
+    if let Some(var) = ast::Var::from_expr(expr) {
+        if !bound.contains(&var.name) {
+            free.insert(String::from(var.name.as_str()));
+        }
+    } else if let Some(abs) = ast::Abs::from_expr(expr) {
+        bound.push(abs.binder.name.clone());
+        let res = free_vars(&abs.body, bound, free);
+        bound.pop();
+        res?;
+    } else if let Some(app) = ast::App::from_expr(expr) {
+        free_vars(&app.fun, bound, free)?;
+        free_vars(&app.arg, bound, free)?;
+    } else if let Some(let_) = ast::Let::from_expr(expr) {
+        free_vars(&let_.bindee, bound, free)?;
+        bound.push(let_.binder.name.clone());
+        let res = free_vars(&let_.body, bound, free);
+        bound.pop();
+        res?;
+    } else if ast::Unit::from_expr(expr).is_none() {
+        return Err(TransformError::from_expr(expr));
+    }
+    Ok(())
+}
+
+struct Uniquify<'a> {
+    fresh: &'a mut Fresh,
+    taken: HashSet<String>,
+    scope: Vec<(Ident, Ident)>,
+}
+
+impl<'a> Uniquify<'a> {
+    fn bind(&mut self, binder: &Binder) -> Binder {
+        let name = if self.taken.contains(binder.name.as_str()) {
+            self.fresh.next(binder.name.as_str())
+        } else {
+            binder.name.clone()
+        };
+        self.taken.insert(String::from(name.as_str()));
+        self.scope.push((binder.name.clone(), name.clone()));
+        Binder {
+            name,
+            annot: binder.annot.clone(),
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) -> Result<Expr> {
+        if let Some(var) = ast::Var::from_expr(expr) {
+            let renamed = self
+                .scope
+                .iter()
+                .rev()
+                .find(|(name, _)| *name == var.name)
+                .map_or(&var.name, |(_, renamed)| renamed);
+            Ok(ast::var(renamed.as_str()))
+        } else if let Some(abs) = ast::Abs::from_expr(expr) {
+            let binder = self.bind(&abs.binder);
+            let body = self.expr(&abs.body);
+            self.scope.pop();
+            Ok(ast::abs(binder, body?))
+        } else if let Some(app) = ast::App::from_expr(expr) {
+            Ok(ast::app(self.expr(&app.fun)?, self.expr(&app.arg)?))
+        } else if let Some(let_) = ast::Let::from_expr(expr) {
+            let bindee = self.expr(&let_.bindee)?;
+            let binder = self.bind(&let_.binder);
+            let body = self.expr(&let_.body);
+            self.scope.pop();
+            Ok(ast::let_(binder, bindee, body?))
+        } else if ast::Unit::from_expr(expr).is_some() {
+            Ok(ast::unit())
+        } else {
+            Err(TransformError::from_expr(expr))
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use std::{fmt::Display, rc::Rc};
+
+use felix_type_checker::ast::{self, Expr, FromExpr};
+
+pub mod anf;
+pub mod church;
+pub mod cps;
+mod fresh;
+pub mod ski;
+
+#[derive(Debug)]
+pub enum TransformError {
+    BrokenNode(Rc<ast::Broken>),
+    UnsupportedNode(Expr),
+}
+
+pub type Result<T> = std::result::Result<T, TransformError>;
+
+impl TransformError {
+    fn from_expr(expr: &Expr) -> Self {
+        match ast::Broken::from_expr(expr) {
+            Some(broken) => Self::BrokenNode(broken),
+            None => Self::UnsupportedNode(expr.clone()),
+        }
+    }
+}
+
+impl Display for TransformError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BrokenNode(_) => write!(f, "program contains syntax errors"),
+            Self::UnsupportedNode(expr) => write!(f, "unsupported expression: {}", expr),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
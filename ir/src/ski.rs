@@ -0,0 +1,192 @@
+//! Combinatory logic: bracket abstraction eliminates all lambdas in favour
+//! of a fixed set of combinators.
+use std::{fmt::Display, rc::Rc};
+
+use felix_type_checker::ast::{self, FromExpr, Ident};
+
+use crate::{Result, TransformError};
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Comb {
+    /// `S f g x = f x (g x)`
+    S,
+    /// `K x y = x`
+    K,
+    /// `I x = x`
+    I,
+    /// `B f g x = f (g x)`
+    B,
+    /// `C f x y = f y x`
+    C,
+}
+
+#[derive(Clone, Debug)]
+pub enum Term {
+    Comb(Comb),
+    Var(Ident),
+    Unit,
+    App(Rc<Term>, Rc<Term>),
+}
+
+/// The combinators bracket abstraction may use.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Basis {
+    /// Only `S`, `K` and `I`.
+    SKI,
+    /// Additionally `B` and `C`, together with η-reduction. This avoids the
+    /// exponential blow-up of the plain `S`/`K`/`I` translation in many
+    /// cases.
+    SKIBC,
+}
+
+/// Translate an expression into a combinator term. Type annotations are
+/// dropped and `let x = e1 in e2` is treated as `(λx. e2) e1`. Free variables
+/// of the expression remain variables, closed expressions become pure
+/// combinator terms.
+pub fn convert(expr: &ast::Expr, basis: Basis) -> Result<Term> {
+    if let Some(var) = ast::Var::from_expr(expr) {
+        Ok(Term::Var(var.name.clone()))
+    } else if let Some(abs) = ast::Abs::from_expr(expr) {
+        let body = convert(&abs.body, basis)?;
+        Ok(abstract_var(&abs.binder.name, &body, basis))
+    } else if let Some(app) = ast::App::from_expr(expr) {
+        let fun = convert(&app.fun, basis)?;
+        let arg = convert(&app.arg, basis)?;
+        Ok(Term::app(fun, arg))
+    } else if let Some(let_) = ast::Let::from_expr(expr) {
+        let bindee = convert(&let_.bindee, basis)?;
+        let body = convert(&let_.body, basis)?;
+        Ok(Term::app(
+            abstract_var(&let_.binder.name, &body, basis),
+            bindee,
+        ))
+    } else if ast::Unit::from_expr(expr).is_some() {
+        Ok(Term::Unit)
+    } else {
+        Err(TransformError::from_expr(expr))
+    }
+}
+
+/// Compute `[x] term`, a term without free occurrences of `x` such that
+/// `([x] term) x` reduces to `term`.
+fn abstract_var(x: &Ident, term: &Term, basis: Basis) -> Term {
+    if !term.has_free(x) {
+        return Term::app(Term::Comb(Comb::K), term.clone());
+    }
+    match term {
+        Term::Var(_) => Term::Comb(Comb::I),
+        Term::App(fun, arg) => match basis {
+            Basis::SKI => Term::app(
+                Term::app(Term::Comb(Comb::S), abstract_var(x, fun, basis)),
+                abstract_var(x, arg, basis),
+            ),
+            Basis::SKIBC => {
+                let fun_free = fun.has_free(x);
+                match arg.as_ref() {
+                    Term::Var(name) if name == x && !fun_free => fun.as_ref().clone(),
+                    _ if !fun_free => Term::app(
+                        Term::app(Term::Comb(Comb::B), fun.as_ref().clone()),
+                        abstract_var(x, arg, basis),
+                    ),
+                    _ if !arg.has_free(x) => Term::app(
+                        Term::app(Term::Comb(Comb::C), abstract_var(x, fun, basis)),
+                        arg.as_ref().clone(),
+                    ),
+                    _ => Term::app(
+                        Term::app(Term::Comb(Comb::S), abstract_var(x, fun, basis)),
+                        abstract_var(x, arg, basis),
+                    ),
+                }
+            }
+        },
+        Term::Comb(_) | Term::Unit => unreachable!("term without free variables"),
+    }
+}
+
+impl Term {
+    fn app(fun: Term, arg: Term) -> Self {
+        Self::App(Rc::new(fun), Rc::new(arg))
+    }
+
+    fn has_free(&self, x: &Ident) -> bool {
+        match self {
+            Self::Var(name) => name == x,
+            Self::App(fun, arg) => fun.has_free(x) || arg.has_free(x),
+            Self::Comb(_) | Self::Unit => false,
+        }
+    }
+
+    /// Number of combinators, variables and constants in the term.
+    pub fn size(&self) -> usize {
+        match self {
+            Self::App(fun, arg) => fun.size() + arg.size(),
+            Self::Comb(_) | Self::Var(_) | Self::Unit => 1,
+        }
+    }
+
+    /// Read the term back into the surface AST. Combinators are replaced by
+    /// their definitions as lambda terms.
+    pub fn to_expr(&self) -> ast::Expr {
+        match self {
+            Self::Comb(comb) => comb.to_expr(),
+            Self::Var(name) => ast::var(name.as_str()),
+            Self::Unit => ast::unit(),
+            Self::App(fun, arg) => ast::app(fun.to_expr(), arg.to_expr()),
+        }
+    }
+}
+
+impl Comb {
+    fn to_expr(self) -> ast::Expr {
+        use ast::{abs, app, binder, var};
+        match self {
+            Self::S => abs(
+                binder("f"),
+                abs(
+                    binder("g"),
+                    abs(
+                        binder("x"),
+                        app(app(var("f"), var("x")), app(var("g"), var("x"))),
+                    ),
+                ),
+            ),
+            Self::K => abs(binder("x"), abs(binder("y"), var("x"))),
+            Self::I => abs(binder("x"), var("x")),
+            Self::B => abs(
+                binder("f"),
+                abs(
+                    binder("g"),
+                    abs(binder("x"), app(var("f"), app(var("g"), var("x")))),
+                ),
+            ),
+            Self::C => abs(
+                binder("f"),
+                abs(
+                    binder("x"),
+                    abs(binder("y"), app(app(var("f"), var("y")), var("x"))),
+                ),
+            ),
+        }
+    }
+}
+
+impl Display for Comb {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Display for Term {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Comb(comb) => write!(f, "{}", comb),
+            Self::Var(name) => write!(f, "{}", name.as_str()),
+            Self::Unit => write!(f, "unit"),
+            Self::App(fun, arg) => match arg.as_ref() {
+                Self::App(..) => write!(f, "{} ({})", fun, arg),
+                _ => write!(f, "{} {}", fun, arg),
+            },
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use assert_matches::assert_matches;
+use insta::assert_snapshot;
+
+use felix_interpreter::Value;
+use felix_type_checker::{ast::*, stlc, Checker, Context, Type};
+
+use super::*;
+
+fn eval(expr: &Expr) -> felix_interpreter::Result<Value> {
+    felix_interpreter::eval(&felix_core::erase(expr).unwrap())
+}
+
+fn unit_to_unit() -> Type {
+    Type::Arrow(Type::Unit.into(), Type::Unit.into())
+}
+
+fn twice() -> Expr {
+    let_(
+        binder("twice"),
+        abs(
+            binder_annot("f", unit_to_unit()),
+            abs(
+                binder_annot("u", Type::Unit),
+                app(var("f"), app(var("f"), var("u"))),
+            ),
+        ),
+        app(
+            app(var("twice"), abs(binder_annot("u", Type::Unit), var("u"))),
+            unit(),
+        ),
+    )
+}
+
+/// Moving the inner `let` in front of the application must not capture the
+/// `x` in function position.
+fn capture() -> Expr {
+    let_(
+        binder("x"),
+        abs(binder_annot("z", Type::Unit), var("z")),
+        app(var("x"), let_(binder("x"), unit(), var("x"))),
+    )
+}
+
+fn church_exponentiation() -> Expr {
+    let two = abs(
+        binder("f"),
+        abs(binder("x"), app(var("f"), app(var("f"), var("x")))),
+    );
+    let step = abs(binder("k"), abs(binder("u"), app(var("k"), var("u"))));
+    let_(
+        binder("two"),
+        two,
+        let_(
+            binder("n"),
+            app(app(var("two"), var("two")), var("two")),
+            app(app(app(var("n"), step), abs(binder("u"), var("u"))), unit()),
+        ),
+    )
+}
+
+fn corpus() -> Vec<(&'static str, Expr)> {
+    vec![
+        ("unit", unit()),
+        ("id", abs(binder("x"), var("x"))),
+        ("app", app(abs(binder("x"), var("x")), unit())),
+        (
+            "app_app",
+            app(
+                app(abs(binder("x"), var("x")), abs(binder("y"), var("y"))),
+                unit(),
+            ),
+        ),
+        (
+            "let_fun",
+            app(
+                let_(binder("f"), abs(binder("x"), var("x")), var("f")),
+                unit(),
+            ),
+        ),
+        (
+            "let_bindee",
+            let_(binder("x"), let_(binder("y"), unit(), var("y")), var("x")),
+        ),
+        (
+            "static_scoping",
+            let_(
+                binder("x"),
+                unit(),
+                let_(
+                    binder("f"),
+                    abs(binder("y"), var("x")),
+                    let_(
+                        binder("x"),
+                        abs(binder("z"), var("z")),
+                        app(var("f"), var("x")),
+                    ),
+                ),
+            ),
+        ),
+        ("twice", twice()),
+        ("capture", capture()),
+        ("church_exponentiation", church_exponentiation()),
+    ]
+}
+
+fn assert_same_result(name: &str, expected: &Expr, actual: &Expr) {
+    let expected = eval(expected).unwrap();
+    let actual = eval(actual).unwrap_or_else(|error| panic!("{}: {:?}", name, error));
+    match (&expected, &actual) {
+        (Value::Unit, Value::Unit) | (Value::Closure(_), Value::Closure(_)) => {}
+        _ => panic!("{}: expected {}, found {}", name, expected, actual),
+    }
+}
+
+#[test]
+fn anf_preserves_results() {
+    for (name, expr) in corpus() {
+        let anf = anf::convert(&expr).unwrap();
+        assert_same_result(name, &expr, &anf.to_expr());
+    }
+}
+
+#[test]
+fn anf_preserves_types() {
+    let checker = stlc::get();
+    for expr in [twice(), capture()] {
+        let expected = checker.infer(&Context::new(), &expr).unwrap();
+        let anf = anf::convert(&expr).unwrap();
+        let actual = checker.infer(&Context::new(), &anf.to_expr()).unwrap();
+        assert_eq!(actual, expected);
+    }
+}
+
+#[test]
+fn cps_preserves_results() {
+    for (name, expr) in corpus() {
+        let cps = cps::convert(&expr).unwrap();
+        assert_same_result(name, &expr, &cps.to_expr());
+    }
+}
+
+#[test]
+fn ski_preserves_results() {
+    for basis in [ski::Basis::SKI, ski::Basis::SKIBC] {
+        for (name, expr) in corpus() {
+            let term = ski::convert(&expr, basis).unwrap();
+            assert_same_result(name, &expr, &term.to_expr());
+        }
+    }
+}
+
+#[test]
+fn ski_bc_is_smaller() {
+    for (name, expr) in corpus() {
+        let plain = ski::convert(&expr, ski::Basis::SKI).unwrap();
+        let optimised = ski::convert(&expr, ski::Basis::SKIBC).unwrap();
+        assert!(optimised.size() <= plain.size(), "{}", name);
+    }
+}
+
+#[test]
+fn church_preserves_results() {
+    for (name, expr) in corpus() {
+        let church = church::convert(&expr).unwrap();
+        match eval(&expr).unwrap() {
+            Value::Unit => assert_same_result(name, &expr, &church::decode_unit(church)),
+            Value::Closure(_) => assert_same_result(name, &expr, &church),
+        }
+    }
+}
+
+#[test]
+fn ski_broken() {
+    let expr = abs(binder("x"), app(var("x"), broken()));
+    assert_matches!(
+        ski::convert(&expr, ski::Basis::SKI),
+        Err(TransformError::BrokenNode(_))
+    );
+}
+
+#[test]
+fn church_broken() {
+    let expr = let_(binder("x"), broken(), unit());
+    assert_matches!(church::convert(&expr), Err(TransformError::BrokenNode(_)));
+}
+
+#[test]
+fn anf_broken() {
+    let expr = app(var("f"), broken());
+    assert_matches!(anf::convert(&expr), Err(TransformError::BrokenNode(_)));
+}
+
+#[test]
+fn cps_broken() {
+    let expr = abs(binder("x"), broken());
+    assert_matches!(cps::convert(&expr), Err(TransformError::BrokenNode(_)));
+}
+
+#[test]
+fn anf_display_twice() {
+    assert_snapshot!(anf::convert(&twice()).unwrap(), @r"
+    let twice = λf:(Unit -> Unit). λu:Unit.
+        let %2 = f u in
+        f %2
+    in
+    let %3 = twice (λu%1:Unit. u%1) in
+    %3 unit
+    ");
+}
+
+#[test]
+fn anf_display_capture() {
+    assert_snapshot!(anf::convert(&capture()).unwrap(), @r"
+    let x = λz:Unit. z in
+    let x%1 = unit in
+    x x%1
+    ");
+}
+
+#[test]
+fn cps_display_twice() {
+    assert_snapshot!(cps::convert(&twice()).unwrap(), @"(λtwice. twice (λu%1:Unit k%2. k%2 u%1) (λ%3. %3 unit (λ%4. halt %4))) (λf:(Unit -> Unit) k%5. k%5 (λu:Unit k%6. f u (λ%7. f %7 k%6)))");
+}
+
+#[test]
+fn cps_display_capture() {
+    assert_snapshot!(cps::convert(&capture()).unwrap(), @"(λx. (λx%1. x x%1 (λ%2. halt %2)) unit) (λz:Unit k%3. k%3 z)");
+}
+
+#[test]
+fn ski_display_const() {
+    let expr = abs(binder("x"), abs(binder("y"), var("x")));
+    assert_snapshot!(ski::convert(&expr, ski::Basis::SKI).unwrap(), @"S (K K) I");
+    assert_snapshot!(ski::convert(&expr, ski::Basis::SKIBC).unwrap(), @"K");
+}
+
+#[test]
+fn ski_display_twice() {
+    assert_snapshot!(ski::convert(&twice(), ski::Basis::SKI).unwrap(), @"S (S I (K I)) (K unit) (S (S (K S) (S (K K) I)) (S (S (K S) (S (K K) I)) (K I)))");
+    assert_snapshot!(ski::convert(&twice(), ski::Basis::SKIBC).unwrap(), @"C (C I I) unit (S B I)");
+}
+
+#[test]
+fn church_display_twice() {
+    assert_snapshot!(church::convert(&twice()).unwrap(), @"let twice = λf. λu. f (f u) in twice (λu. u) (λu. u)");
+}
@@ -0,0 +1,260 @@
+//! The language features on top of a single document. Everything in here
+//! is independent of the protocol machinery in `server`.
+use felix_common::{
+    srcloc::{Encoding, Mapper},
+    Problem, Severity, SrcLoc, SrcSpan,
+};
+use felix_parser::{
+    docs,
+    syntax::{Node, NodeKind, SyntaxKind, COMMENTS},
+    ParseResult, Parser,
+};
+use felix_type_checker::{
+    ast::{self, Abs, App, FromExpr, Let},
+    lower::lower,
+    stlc, Checker, Context, Type,
+};
+use lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, DocumentSymbol, Hover,
+    HoverContents, Location, MarkupContent, MarkupKind, NumberOrString, Position, Range,
+    SymbolKind, TextDocumentContentChangeEvent, TextEdit, Uri,
+};
+
+pub struct Document {
+    mapper: Mapper,
+}
+
+impl Document {
+    pub fn new(text: String) -> Self {
+        // NOTE: LSP counts columns in UTF-16 code units by default.
+        Self {
+            mapper: Mapper::new(text).with_encoding(Encoding::Utf16),
+        }
+    }
+
+    /// Apply a change sent by the client. Changes without a range replace
+    /// the whole document. Returns `false` and leaves the document alone if
+    /// the range is not within the document.
+    pub fn change(&mut self, change: TextDocumentContentChangeEvent) -> bool {
+        let Some(range) = change.range else {
+            *self = Self::new(change.text);
+            return true;
+        };
+        match (self.offset(range.start), self.offset(range.end)) {
+            (Some(start), Some(end)) if start <= end => {
+                self.mapper.edit(SrcSpan { start, end }, &change.text);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn parse(&self) -> ParseResult {
+        Parser::new(self.mapper.text(), &self.mapper).run(Parser::program)
+    }
+
+    /// The problems of the parser and, if there are none, the type checker.
+    pub fn diagnostics(&self, uri: &Uri) -> Vec<Diagnostic> {
+        let result = self.parse();
+        if !result.problems.is_empty() {
+            return result
+                .problems
+                .iter()
+                .map(|problem| diagnostic(uri, problem))
+                .collect();
+        }
+        let expr = lower(&result.syntax);
+        match stlc::get().infer(&Context::new(), &expr) {
+            Ok(_) => Vec::new(),
+            Err(error) => vec![diagnostic(uri, &error.to_problem(&self.mapper, &expr))],
+        }
+    }
+
+    /// The type of the innermost expression at the position which can be
+    /// typed. Variables bound by a `let` also show its doc comments.
+    pub fn hover(&self, position: Position) -> Option<Hover> {
+        let offset = self.offset(position)?;
+        let result = self.parse();
+        let expr = lower(&result.syntax);
+        let (expr, r#type) = type_at(&Context::new(), &expr, offset)?;
+        let mut value = format!("```felix\n{} : {}\n```", expr, r#type);
+        let docs = expr.span().and_then(|span| {
+            let var = result.syntax.descendants().find(|node| {
+                node.kind() == SyntaxKind::Node(NodeKind::EXPR_VAR) && node_span(node) == span
+            })?;
+            docs::docs(&docs::binder_of(&var)?)
+        });
+        if let Some(docs) = docs {
+            value.push_str("\n\n");
+            value.push_str(&docs);
+        }
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }),
+            range: expr.span().map(|span| self.range(span)),
+        })
+    }
+
+    /// All variables bound by `let`s, in the order of the source code.
+    pub fn symbols(&self) -> Vec<DocumentSymbol> {
+        let result = self.parse();
+        result
+            .syntax
+            .descendants()
+            .filter(|node| node.kind() == SyntaxKind::Node(NodeKind::EXPR_LET))
+            .filter_map(|node| {
+                let name = node
+                    .children()
+                    .find(|child| child.kind() == SyntaxKind::Node(NodeKind::BINDER))?
+                    .children()
+                    .find(|child| child.kind() == SyntaxKind::Node(NodeKind::NAME))?;
+                #[allow(deprecated)]
+                Some(DocumentSymbol {
+                    name: name.text().to_string(),
+                    detail: None,
+                    kind: SymbolKind::VARIABLE,
+                    tags: None,
+                    deprecated: None,
+                    range: self.range(node_span(&node)),
+                    selection_range: self.range(node_span(&name)),
+                    children: None,
+                })
+            })
+            .collect()
+    }
+
+    /// Replace the whole document with its canonical layout. Documents with
+    /// problems or comments are left alone since formatting would lose
+    /// parts of them.
+    pub fn format(&self) -> Option<Vec<TextEdit>> {
+        let result = self.parse();
+        let has_comments =
+            result
+                .syntax
+                .descendants_with_tokens()
+                .any(|element| match element.kind() {
+                    SyntaxKind::Token(token) => token.is(COMMENTS),
+                    SyntaxKind::Node(_) => false,
+                });
+        if !result.problems.is_empty() || has_comments {
+            return None;
+        }
+        let span = SrcSpan {
+            start: 0,
+            end: self.mapper.text().len() as u32,
+        };
+        Some(vec![TextEdit {
+            range: self.range(span),
+            new_text: format!("{}\n", lower(&result.syntax)),
+        }])
+    }
+
+    fn range(&self, span: SrcSpan<u32>) -> Range {
+        Range::new(
+            position(self.mapper.src_loc(span.start)),
+            position(self.mapper.src_loc(span.end)),
+        )
+    }
+
+    /// Convert an LSP position into a byte offset. Returns `None` if the
+    /// position is not within the document.
+    fn offset(&self, position: Position) -> Option<u32> {
+        self.mapper
+            .index(SrcLoc::new(position.line, position.character))
+    }
+}
+
+fn diagnostic(uri: &Uri, problem: &Problem) -> Diagnostic {
+    let severity = match problem.severity {
+        Severity::ERROR => DiagnosticSeverity::ERROR,
+        Severity::WARNING => DiagnosticSeverity::WARNING,
+        Severity::INFO => DiagnosticSeverity::INFORMATION,
+        Severity::HINT => DiagnosticSeverity::HINT,
+    };
+    // NOTE: LSP has no notes. Clients show the message verbatim, so we put
+    // them on lines of their own below it.
+    let message = std::iter::once(problem.message.clone())
+        .chain(problem.notes.iter().map(|note| format!("note: {}", note)))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let related_information = problem
+        .labels
+        .iter()
+        .map(|label| DiagnosticRelatedInformation {
+            location: Location::new(
+                uri.clone(),
+                Range::new(position(label.start), position(label.end)),
+            ),
+            message: label.message.clone(),
+        })
+        .collect::<Vec<_>>();
+    Diagnostic {
+        range: Range::new(position(problem.start), position(problem.end)),
+        severity: Some(severity),
+        code: Some(NumberOrString::String(problem.code.to_string())),
+        source: Some(problem.source.clone()),
+        message,
+        related_information: (!related_information.is_empty()).then_some(related_information),
+        // NOTE: Clients pass `data` back to us in code action requests.
+        data: (!problem.expected.is_empty())
+            .then(|| serde_json::json!({ "expected": problem.expected })),
+        ..Diagnostic::default()
+    }
+}
+
+fn position(src_loc: SrcLoc) -> Position {
+    Position::new(src_loc.line, src_loc.column)
+}
+
+fn node_span(node: &Node) -> SrcSpan<u32> {
+    let range = node.text_range();
+    SrcSpan {
+        start: range.start().into(),
+        end: range.end().into(),
+    }
+}
+
+fn contains(expr: &ast::Expr, offset: u32) -> bool {
+    expr.span()
+        .is_some_and(|span| span.start <= offset && offset <= span.end)
+}
+
+/// Find the innermost expression containing the offset whose type can be
+/// inferred, together with its type.
+fn type_at(ctx: &Context, expr: &ast::Expr, offset: u32) -> Option<(ast::Expr, Type)> {
+    if !contains(expr, offset) {
+        return None;
+    }
+    let checker = stlc::get();
+    let inner = if let Some(let_) = Let::from_expr(expr) {
+        if contains(&let_.bindee, offset) {
+            type_at(ctx, &let_.bindee, offset)
+        } else {
+            let r#type = match &let_.binder.annot {
+                Some(r#type) => Some(r#type.clone()),
+                None => checker.infer(ctx, &let_.bindee).ok(),
+            };
+            r#type.and_then(|r#type| {
+                let ctx = ctx.extend(let_.binder.name.clone(), r#type);
+                type_at(&ctx, &let_.body, offset)
+            })
+        }
+    } else if let Some(abs) = Abs::from_expr(expr) {
+        // NOTE: Lambdas without annotation cannot be typed and hence we
+        // know nothing about the types inside their body either.
+        abs.binder.annot.as_ref().and_then(|r#type| {
+            let ctx = ctx.extend(abs.binder.name.clone(), r#type.clone());
+            type_at(&ctx, &abs.body, offset)
+        })
+    } else if let Some(app) = App::from_expr(expr) {
+        type_at(ctx, &app.fun, offset).or_else(|| type_at(ctx, &app.arg, offset))
+    } else {
+        None
+    };
+    inner.or_else(|| {
+        let r#type = checker.infer(ctx, expr).ok()?;
+        Some((expr.clone(), r#type))
+    })
+}
@@ -0,0 +1,24 @@
+//! The `felix-lsp` language server. It speaks the Language Server Protocol
+//! over stdio.
+use std::process::ExitCode;
+
+use lsp_server::Connection;
+
+mod analysis;
+mod server;
+
+fn main() -> ExitCode {
+    let (connection, io_threads) = Connection::stdio();
+    let result = server::run(&connection);
+    drop(connection);
+    match result.and_then(|()| Ok(io_threads.join()?)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("felix-lsp: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,183 @@
+//! The protocol side of the language server: the lifecycle of the
+//! connection, the synchronisation of documents and the dispatch of
+//! requests to `analysis`.
+use std::{collections::HashMap, error::Error};
+
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _,
+        PublishDiagnostics,
+    },
+    request::{DocumentSymbolRequest, Formatting, HoverRequest, Request as _},
+    DocumentFormattingParams, DocumentSymbolParams, DocumentSymbolResponse, Hover, HoverParams,
+    HoverProviderCapability, OneOf, PublishDiagnosticsParams, ServerCapabilities,
+    TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Uri,
+};
+
+use crate::analysis::Document;
+
+pub type Result<T> = std::result::Result<T, Box<dyn Error + Send + Sync>>;
+
+pub fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(
+            TextDocumentSyncKind::INCREMENTAL,
+        )),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        document_formatting_provider: Some(OneOf::Left(true)),
+        ..ServerCapabilities::default()
+    }
+}
+
+/// Serve the client on the other end of the connection until it shuts the
+/// server down.
+pub fn run(connection: &Connection) -> Result<()> {
+    connection.initialize(serde_json::to_value(capabilities())?)?;
+    let mut server = Server {
+        connection,
+        documents: HashMap::new(),
+    };
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    return Ok(());
+                }
+                server.handle_request(request)?;
+            }
+            Message::Notification(notification) => server.handle_notification(notification)?,
+            Message::Response(_) => {}
+        }
+    }
+    Ok(())
+}
+
+struct Server<'a> {
+    connection: &'a Connection,
+    documents: HashMap<Uri, Document>,
+}
+
+impl Server<'_> {
+    fn handle_request(&self, request: Request) -> Result<()> {
+        let response = match request.method.as_str() {
+            HoverRequest::METHOD => self.respond::<HoverRequest>(request, Self::hover),
+            DocumentSymbolRequest::METHOD => {
+                self.respond::<DocumentSymbolRequest>(request, Self::symbols)
+            }
+            Formatting::METHOD => self.respond::<Formatting>(request, Self::format),
+            method => Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("unsupported request: {}", method),
+            ),
+        };
+        self.connection.sender.send(response.into())?;
+        Ok(())
+    }
+
+    fn respond<R: lsp_types::request::Request>(
+        &self,
+        request: Request,
+        handler: fn(&Self, R::Params) -> R::Result,
+    ) -> Response {
+        match serde_json::from_value(request.params) {
+            Ok(params) => Response::new_ok(request.id, handler(self, params)),
+            Err(error) => Response::new_err(
+                request.id,
+                ErrorCode::InvalidParams as i32,
+                error.to_string(),
+            ),
+        }
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> Result<()> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let Some(params) = params::<DidOpenTextDocument>(notification) else {
+                    return Ok(());
+                };
+                let document = params.text_document;
+                self.documents
+                    .insert(document.uri.clone(), Document::new(document.text));
+                self.check(document.uri)
+            }
+            DidChangeTextDocument::METHOD => {
+                let Some(params) = params::<DidChangeTextDocument>(notification) else {
+                    return Ok(());
+                };
+                let uri = params.text_document.uri;
+                let Some(document) = self.documents.get_mut(&uri) else {
+                    return Ok(());
+                };
+                // NOTE: The changes are applied one after the other. A change
+                // outside of the document means we are out of sync with the
+                // client and skip it rather than guessing.
+                for change in params.content_changes {
+                    document.change(change);
+                }
+                self.check(uri)
+            }
+            DidCloseTextDocument::METHOD => {
+                let Some(params) = params::<DidCloseTextDocument>(notification) else {
+                    return Ok(());
+                };
+                let uri = params.text_document.uri;
+                self.documents.remove(&uri);
+                self.publish(uri, Vec::new())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Publish the diagnostics of an open document.
+    fn check(&self, uri: Uri) -> Result<()> {
+        let diagnostics = match self.documents.get(&uri) {
+            Some(document) => document.diagnostics(&uri),
+            None => Vec::new(),
+        };
+        self.publish(uri, diagnostics)
+    }
+
+    fn publish(&self, uri: Uri, diagnostics: Vec<lsp_types::Diagnostic>) -> Result<()> {
+        let params = PublishDiagnosticsParams {
+            uri,
+            diagnostics,
+            version: None,
+        };
+        let notification = Notification::new(PublishDiagnostics::METHOD.to_string(), params);
+        self.connection.sender.send(notification.into())?;
+        Ok(())
+    }
+
+    fn hover(&self, params: HoverParams) -> Option<Hover> {
+        let position = params.text_document_position_params;
+        self.documents
+            .get(&position.text_document.uri)?
+            .hover(position.position)
+    }
+
+    fn symbols(&self, params: DocumentSymbolParams) -> Option<DocumentSymbolResponse> {
+        let document = self.documents.get(&params.text_document.uri)?;
+        Some(DocumentSymbolResponse::Nested(document.symbols()))
+    }
+
+    fn format(&self, params: DocumentFormattingParams) -> Option<Vec<TextEdit>> {
+        self.documents.get(&params.text_document.uri)?.format()
+    }
+}
+
+/// The params of a notification. Notifications cannot be answered with an
+/// error, so malformed params are logged and the notification is ignored.
+fn params<N: lsp_types::notification::Notification>(
+    notification: Notification,
+) -> Option<N::Params> {
+    match serde_json::from_value(notification.params) {
+        Ok(params) => Some(params),
+        Err(error) => {
+            eprintln!("felix-lsp: ignoring {}: {}", notification.method, error);
+            None
+        }
+    }
+}
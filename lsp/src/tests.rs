@@ -0,0 +1,331 @@
+use std::thread::JoinHandle;
+
+use lsp_server::{Connection, Message, Notification, Request, RequestId, Response};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Exit, Initialized,
+        Notification as _, PublishDiagnostics,
+    },
+    request::{DocumentSymbolRequest, Formatting, HoverRequest, Initialize, Shutdown},
+    DidChangeTextDocumentParams, DidOpenTextDocumentParams, DocumentFormattingParams,
+    DocumentSymbolParams, DocumentSymbolResponse, FormattingOptions, Hover, HoverContents,
+    HoverParams, InitializeResult, NumberOrString, Position, PublishDiagnosticsParams, Range,
+    TextDocumentContentChangeEvent, TextDocumentIdentifier, TextDocumentItem,
+    TextDocumentPositionParams, TextEdit, Uri, VersionedTextDocumentIdentifier,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::server;
+
+/// A client talking to a server running in another thread over an
+/// in-memory connection.
+struct MockClient {
+    connection: Connection,
+    server: Option<JoinHandle<()>>,
+    next_id: i32,
+}
+
+impl MockClient {
+    fn start() -> Self {
+        let (connection, server_connection) = Connection::memory();
+        let server = std::thread::spawn(move || server::run(&server_connection).unwrap());
+        let mut client = Self {
+            connection,
+            server: Some(server),
+            next_id: 0,
+        };
+        let result: InitializeResult =
+            client.request::<Initialize>(serde_json::from_str("{\"capabilities\": {}}").unwrap());
+        assert!(result.capabilities.hover_provider.is_some());
+        client.notify::<Initialized>(serde_json::from_str("{}").unwrap());
+        client
+    }
+
+    fn send_request<P: Serialize>(&mut self, method: &str, params: P) -> Response {
+        self.next_id += 1;
+        let id = RequestId::from(self.next_id);
+        let request = Request::new(id.clone(), method.to_string(), params);
+        self.connection.sender.send(request.into()).unwrap();
+        match self.connection.receiver.recv().unwrap() {
+            Message::Response(response) if response.id == id => response,
+            message => panic!("expected response, got {:?}", message),
+        }
+    }
+
+    fn request<R: lsp_types::request::Request>(&mut self, params: R::Params) -> R::Result {
+        let response = self.send_request(R::METHOD, params);
+        assert!(response.error.is_none(), "{:?}", response.error);
+        serde_json::from_value(response.result.unwrap()).unwrap()
+    }
+
+    fn notify<N: lsp_types::notification::Notification>(&self, params: N::Params) {
+        let notification = Notification::new(N::METHOD.to_string(), params);
+        self.connection.sender.send(notification.into()).unwrap();
+    }
+
+    fn receive<N>(&self) -> N::Params
+    where
+        N: lsp_types::notification::Notification,
+        N::Params: DeserializeOwned,
+    {
+        match self.connection.receiver.recv().unwrap() {
+            Message::Notification(notification) if notification.method == N::METHOD => {
+                serde_json::from_value(notification.params).unwrap()
+            }
+            message => panic!("expected {}, got {:?}", N::METHOD, message),
+        }
+    }
+
+    /// Open a document and return the diagnostics published for it.
+    fn open(&self, text: &str) -> PublishDiagnosticsParams {
+        self.notify::<DidOpenTextDocument>(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: uri(),
+                language_id: String::from("felix"),
+                version: 0,
+                text: text.to_string(),
+            },
+        });
+        self.receive::<PublishDiagnostics>()
+    }
+}
+
+impl Drop for MockClient {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            return;
+        }
+        self.request::<Shutdown>(());
+        self.notify::<Exit>(());
+        self.server.take().unwrap().join().unwrap();
+    }
+}
+
+fn uri() -> Uri {
+    "file:///test.fx".parse().unwrap()
+}
+
+fn range(start: (u32, u32), end: (u32, u32)) -> Range {
+    Range::new(Position::new(start.0, start.1), Position::new(end.0, end.1))
+}
+
+fn hover(client: &mut MockClient, line: u32, character: u32) -> Option<String> {
+    let hover: Option<Hover> = client.request::<HoverRequest>(HoverParams {
+        text_document_position_params: TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri: uri() },
+            position: Position::new(line, character),
+        },
+        work_done_progress_params: Default::default(),
+    });
+    hover.map(|hover| match hover.contents {
+        HoverContents::Markup(markup) => markup.value,
+        contents => panic!("unexpected hover contents {:?}", contents),
+    })
+}
+
+#[test]
+fn lsp_no_diagnostics() {
+    let client = MockClient::start();
+    let params = client.open("let f = λx:Unit. x in f unit");
+    assert_eq!(params.uri, uri());
+    assert!(params.diagnostics.is_empty());
+}
+
+#[test]
+fn lsp_parse_diagnostics() {
+    let client = MockClient::start();
+    // NOTE: `λ` and `𝔸` take one and two UTF-16 code units, respectively.
+    let params = client.open("# 𝔸\nλx:Unit. )");
+    let [diagnostic] = params.diagnostics.as_slice() else {
+        panic!("expected one diagnostic, got {:?}", params.diagnostics);
+    };
+    assert_eq!(diagnostic.range, range((1, 9), (1, 10)));
+    assert_eq!(diagnostic.source.as_deref(), Some("parser/expr_abs"));
+    assert_eq!(
+        diagnostic.code,
+        Some(NumberOrString::String(String::from("P0001")))
+    );
+}
+
+#[test]
+fn lsp_type_diagnostics() {
+    let client = MockClient::start();
+    let params = client.open("# 𝔸\nunit unit");
+    let [diagnostic] = params.diagnostics.as_slice() else {
+        panic!("expected one diagnostic, got {:?}", params.diagnostics);
+    };
+    assert_eq!(diagnostic.range, range((1, 0), (1, 9)));
+    assert_eq!(diagnostic.message, "expected a function type, found Unit");
+}
+
+#[test]
+fn lsp_nested_type_diagnostics() {
+    let client = MockClient::start();
+    let params = client.open("let x = unit\nin λy:Unit. x x");
+    let [diagnostic] = params.diagnostics.as_slice() else {
+        panic!("expected one diagnostic, got {:?}", params.diagnostics);
+    };
+    assert_eq!(diagnostic.range, range((1, 12), (1, 15)));
+    assert_eq!(diagnostic.message, "expected a function type, found Unit");
+}
+
+#[test]
+fn lsp_malformed_notifications() {
+    let client = MockClient::start();
+    for method in [
+        DidOpenTextDocument::METHOD,
+        DidChangeTextDocument::METHOD,
+        DidCloseTextDocument::METHOD,
+    ] {
+        let notification = Notification::new(method.to_string(), serde_json::json!({}));
+        client.connection.sender.send(notification.into()).unwrap();
+    }
+    assert!(client.open("unit").diagnostics.is_empty());
+}
+
+#[test]
+fn lsp_change() {
+    let client = MockClient::start();
+    assert!(client.open("unit").diagnostics.is_empty());
+    client.notify::<DidChangeTextDocument>(DidChangeTextDocumentParams {
+        text_document: VersionedTextDocumentIdentifier {
+            uri: uri(),
+            version: 1,
+        },
+        content_changes: vec![TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: String::from("unit unit"),
+        }],
+    });
+    let params = client.receive::<PublishDiagnostics>();
+    assert_eq!(params.diagnostics.len(), 1);
+}
+
+#[test]
+fn lsp_incremental_change() {
+    let mut client = MockClient::start();
+    assert_eq!(client.open("# 𝔸\nunit\nunit").diagnostics.len(), 1);
+    let change = |range, text: &str| TextDocumentContentChangeEvent {
+        range: Some(range),
+        range_length: None,
+        text: text.to_string(),
+    };
+    client.notify::<DidChangeTextDocument>(DidChangeTextDocumentParams {
+        text_document: VersionedTextDocumentIdentifier {
+            uri: uri(),
+            version: 1,
+        },
+        content_changes: vec![
+            // Join the last two lines, then wrap the first `unit` in a `let`.
+            change(range((1, 4), (2, 0)), " "),
+            change(range((1, 0), (1, 4)), "let x =\nλy:Unit. y in x"),
+        ],
+    });
+    let params = client.receive::<PublishDiagnostics>();
+    assert!(params.diagnostics.is_empty(), "{:?}", params.diagnostics);
+    assert_eq!(
+        hover(&mut client, 2, 16).unwrap(),
+        "```felix\nunit : Unit\n```"
+    );
+}
+
+#[test]
+fn lsp_hover() {
+    let mut client = MockClient::start();
+    client.open("let f = λx:Unit. x in\nf unit");
+    assert_eq!(
+        hover(&mut client, 1, 0).unwrap(),
+        "```felix\nf : (Unit -> Unit)\n```"
+    );
+    assert_eq!(
+        hover(&mut client, 1, 4).unwrap(),
+        "```felix\nunit : Unit\n```"
+    );
+    assert_eq!(
+        hover(&mut client, 0, 17).unwrap(),
+        "```felix\nx : Unit\n```"
+    );
+    assert_eq!(hover(&mut client, 5, 0), None);
+}
+
+#[test]
+fn lsp_hover_docs() {
+    let mut client = MockClient::start();
+    client.open("## Ignores its argument.\n## Returns `unit`.\nlet f = λx:Unit. unit in\nf ((λf:Unit. f) unit)");
+    assert_eq!(
+        hover(&mut client, 3, 0).unwrap(),
+        "```felix\nf : (Unit -> Unit)\n```\n\nIgnores its argument.\nReturns `unit`."
+    );
+    assert_eq!(
+        hover(&mut client, 3, 13).unwrap(),
+        "```felix\nf : Unit\n```"
+    );
+}
+
+#[test]
+fn lsp_document_symbols() {
+    let mut client = MockClient::start();
+    client.open("let f = let g = unit in g in\nlet h = f in h");
+    let response = client.request::<DocumentSymbolRequest>(DocumentSymbolParams {
+        text_document: TextDocumentIdentifier { uri: uri() },
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    });
+    let Some(DocumentSymbolResponse::Nested(symbols)) = response else {
+        panic!("expected nested symbols, got {:?}", response);
+    };
+    let symbols: Vec<_> = symbols
+        .into_iter()
+        .map(|symbol| (symbol.name, symbol.selection_range))
+        .collect();
+    assert_eq!(
+        symbols,
+        vec![
+            (String::from("f"), range((0, 4), (0, 5))),
+            (String::from("g"), range((0, 12), (0, 13))),
+            (String::from("h"), range((1, 4), (1, 5))),
+        ]
+    );
+}
+
+fn format(client: &mut MockClient) -> Option<Vec<TextEdit>> {
+    client.request::<Formatting>(DocumentFormattingParams {
+        text_document: TextDocumentIdentifier { uri: uri() },
+        options: FormattingOptions {
+            tab_size: 4,
+            insert_spaces: true,
+            ..Default::default()
+        },
+        work_done_progress_params: Default::default(),
+    })
+}
+
+#[test]
+fn lsp_formatting() {
+    let mut client = MockClient::start();
+    client.open("let  f=(λx:Unit.x)in\n  f   unit");
+    assert_eq!(
+        format(&mut client),
+        Some(vec![TextEdit {
+            range: range((0, 0), (1, 10)),
+            new_text: String::from("let f = λx:Unit. x in f unit\n"),
+        }])
+    );
+}
+
+#[test]
+fn lsp_formatting_keeps_comments() {
+    let mut client = MockClient::start();
+    client.open("# the identity\nλx. x");
+    assert_eq!(format(&mut client), None);
+    client.open("#| the identity |# λx. x");
+    assert_eq!(format(&mut client), None);
+}
+
+#[test]
+fn lsp_unsupported_request() {
+    let mut client = MockClient::start();
+    let response = client.send_request("felix/unknown", ());
+    assert!(response.error.is_some());
+}
@@ -0,0 +1,446 @@
+use std::collections::HashSet;
+
+use felix_common::{problem::Suggestion, srcloc::Mapper, Code, Problem};
+
+use crate::{Arm, DataTypes, Decision, Match, Occurrence, Pattern, Type};
+
+const SOURCE: &str = "match";
+
+pub struct MatchResult {
+    pub decision: Decision,
+    pub problems: Vec<Problem>,
+}
+
+/// Compile a match into a decision tree. Undeclared types in the scrutinee
+/// and arms which do not fit its type are reported and result in a tree
+/// that always fails.
+/// Otherwise, the problems report arms which can never be selected and, if
+/// the match is not exhaustive, an example of a value no arm matches.
+pub fn compile(data_types: &DataTypes, r#match: &Match, mapper: &Mapper) -> MatchResult {
+    if let Err(message) = check_type(data_types, &r#match.scrutinee, &mut HashSet::new()) {
+        let problem = mapper.error(
+            r#match.span.start,
+            r#match.span.end,
+            Code::ILL_TYPED_PATTERN,
+            String::from(SOURCE),
+            message,
+        );
+        return MatchResult {
+            decision: Decision::Fail,
+            problems: vec![problem],
+        };
+    }
+
+    let mut problems = Vec::new();
+    for arm in &r#match.arms {
+        if let Err(message) = check_pattern(data_types, &r#match.scrutinee, &arm.pattern) {
+            problems.push(mapper.error(
+                arm.span.start,
+                arm.span.end,
+                Code::ILL_TYPED_PATTERN,
+                String::from(SOURCE),
+                message,
+            ));
+        }
+    }
+    if !problems.is_empty() {
+        return MatchResult {
+            decision: Decision::Fail,
+            problems,
+        };
+    }
+
+    let rows = r#match
+        .arms
+        .iter()
+        .enumerate()
+        .map(|(arm, Arm { pattern, .. })| Row {
+            patterns: vec![pattern.clone()],
+            arm,
+            bindings: Vec::new(),
+        })
+        .collect();
+    let columns = vec![(Occurrence::default(), r#match.scrutinee.clone())];
+    let decision = Compiler { data_types }.compile(rows, columns);
+
+    let mut reachable = HashSet::new();
+    collect_arms(&decision, &mut reachable);
+    for (index, arm) in r#match.arms.iter().enumerate() {
+        if !reachable.contains(&index) {
+            let message = format!(
+                "Redundant arm. The pattern {} only matches values matched by earlier arms.",
+                arm.pattern
+            );
+            let problem = mapper
+                .warning(
+                    arm.span.start,
+                    arm.span.end,
+                    Code::REDUNDANT_ARM,
+                    String::from(SOURCE),
+                    message,
+                )
+                .with_suggestion(Suggestion {
+                    message: String::from("Remove the arm"),
+                    edits: vec![mapper.text_edit(arm.span, String::new())],
+                });
+            problems.push(problem);
+        }
+    }
+    if let Some(path) = find_fail(&decision, &mut Vec::new()) {
+        let witness = Witness {
+            data_types,
+            path: &path,
+        };
+        let example = witness.example(&Occurrence::default(), &r#match.scrutinee);
+        let problem = mapper
+            .error(
+                r#match.span.start,
+                r#match.span.end,
+                Code::NON_EXHAUSTIVE_MATCH,
+                String::from(SOURCE),
+                format!(
+                    "Non-exhaustive match. For example, {} is not matched.",
+                    example
+                ),
+            )
+            .with_note(String::from(
+                "Add an arm for the missing values or a wildcard arm `_` at the end.",
+            ));
+        problems.push(problem);
+    }
+    MatchResult { decision, problems }
+}
+
+/// Check that all sum types reachable from the type, including those of
+/// the fields of its variants, are declared.
+fn check_type<'a>(
+    data_types: &'a DataTypes,
+    r#type: &'a Type,
+    visited: &mut HashSet<&'a str>,
+) -> Result<(), String> {
+    match r#type {
+        Type::Unit => Ok(()),
+        Type::Tuple(types) => types
+            .iter()
+            .try_for_each(|r#type| check_type(data_types, r#type, visited)),
+        Type::Named(sum) => {
+            if !visited.insert(sum) {
+                return Ok(());
+            }
+            let Some(variants) = data_types.variants(sum) else {
+                return Err(format!("Type {} is not declared.", sum));
+            };
+            variants
+                .iter()
+                .flat_map(|variant| &variant.fields)
+                .try_for_each(|r#type| check_type(data_types, r#type, visited))
+        }
+    }
+}
+
+fn check_pattern(data_types: &DataTypes, r#type: &Type, pattern: &Pattern) -> Result<(), String> {
+    match (pattern, r#type) {
+        (Pattern::Wildcard | Pattern::Var(_), _) | (Pattern::Unit, Type::Unit) => Ok(()),
+        (Pattern::Tuple(elems), Type::Tuple(types)) if elems.len() == types.len() => elems
+            .iter()
+            .zip(types)
+            .try_for_each(|(elem, r#type)| check_pattern(data_types, r#type, elem)),
+        (Pattern::Variant(name, args), Type::Named(sum)) => {
+            let Some(variants) = data_types.variants(sum) else {
+                return Err(format!("Type {} is not declared.", sum));
+            };
+            let Some(variant) = variants.iter().find(|variant| variant.name == *name) else {
+                return Err(format!("Type {} has no variant {}.", sum, name));
+            };
+            if args.len() != variant.fields.len() {
+                return Err(format!(
+                    "Variant {} has {} fields, but the pattern has {}.",
+                    name,
+                    variant.fields.len(),
+                    args.len()
+                ));
+            }
+            args.iter()
+                .zip(&variant.fields)
+                .try_for_each(|(arg, r#type)| check_pattern(data_types, r#type, arg))
+        }
+        _ => Err(format!(
+            "Found pattern {}, expected a pattern of type {}.",
+            pattern, r#type
+        )),
+    }
+}
+
+/// A row of the clause matrix.
+#[derive(Clone)]
+struct Row {
+    patterns: Vec<Pattern>,
+    arm: usize,
+    bindings: Vec<(String, Occurrence)>,
+}
+
+impl Row {
+    /// Replace the pattern in column `col` by the sub-patterns `args`.
+    /// Variables become bindings of the occurrence of the column.
+    fn expand(&self, col: usize, occurrence: &Occurrence, args: Vec<Pattern>) -> Row {
+        let mut row = self.clone();
+        if let Pattern::Var(name) = &row.patterns[col] {
+            row.bindings.push((name.clone(), occurrence.clone()));
+        }
+        row.patterns.splice(col..col + 1, args);
+        row
+    }
+}
+
+struct Compiler<'a> {
+    data_types: &'a DataTypes,
+}
+
+impl Compiler<'_> {
+    fn compile(&self, rows: Vec<Row>, columns: Vec<(Occurrence, Type)>) -> Decision {
+        let Some(first) = rows.first() else {
+            // NOTE: Without rows, only values of empty types are matched,
+            // which do not exist. The switch says so by having no cases.
+            return columns
+                .iter()
+                .find_map(|(occurrence, r#type)| self.empty_occurrence(occurrence, r#type))
+                .map_or(Decision::Fail, |occurrence| Decision::Switch {
+                    occurrence,
+                    cases: Vec::new(),
+                    default: None,
+                });
+        };
+        let Some(col) = first
+            .patterns
+            .iter()
+            .position(|pattern| matches!(pattern, Pattern::Tuple(_) | Pattern::Variant(..)))
+        else {
+            // NOTE: All patterns in the first row are irrefutable.
+            let mut bindings = first.bindings.clone();
+            for (pattern, (occurrence, _)) in first.patterns.iter().zip(&columns) {
+                if let Pattern::Var(name) = pattern {
+                    bindings.push((name.clone(), occurrence.clone()));
+                }
+            }
+            return Decision::Leaf {
+                arm: first.arm,
+                bindings,
+            };
+        };
+
+        let (occurrence, r#type) = columns[col].clone();
+        match &r#type {
+            Type::Tuple(types) => {
+                let rows = rows
+                    .iter()
+                    .map(|row| {
+                        let args = match &row.patterns[col] {
+                            Pattern::Tuple(elems) => elems.clone(),
+                            _ => vec![Pattern::Wildcard; types.len()],
+                        };
+                        row.expand(col, &occurrence, args)
+                    })
+                    .collect();
+                let fields = types
+                    .iter()
+                    .enumerate()
+                    .map(|(index, r#type)| (occurrence.field(index), r#type.clone()));
+                self.compile(rows, splice(&columns, col, fields))
+            }
+            Type::Named(sum) => {
+                let variants = self
+                    .data_types
+                    .variants(sum)
+                    .expect("sum types are checked before compilation");
+                let heads: HashSet<&str> = rows
+                    .iter()
+                    .filter_map(|row| match &row.patterns[col] {
+                        Pattern::Variant(name, _) => Some(name.as_str()),
+                        _ => None,
+                    })
+                    .collect();
+                let cases = variants
+                    .iter()
+                    .filter(|variant| heads.contains(variant.name.as_str()))
+                    .map(|variant| {
+                        let rows = rows
+                            .iter()
+                            .filter_map(|row| match &row.patterns[col] {
+                                Pattern::Variant(name, args) => (*name == variant.name)
+                                    .then(|| row.expand(col, &occurrence, args.clone())),
+                                _ => Some(row.expand(
+                                    col,
+                                    &occurrence,
+                                    vec![Pattern::Wildcard; variant.fields.len()],
+                                )),
+                            })
+                            .collect();
+                        let fields = variant
+                            .fields
+                            .iter()
+                            .enumerate()
+                            .map(|(index, r#type)| (occurrence.field(index), r#type.clone()));
+                        let decision = self.compile(rows, splice(&columns, col, fields));
+                        (variant.name.clone(), decision)
+                    })
+                    .collect();
+                let default = (heads.len() < variants.len()).then(|| {
+                    let rows = rows
+                        .iter()
+                        .filter(|row| !matches!(row.patterns[col], Pattern::Variant(..)))
+                        .map(|row| row.expand(col, &occurrence, Vec::new()))
+                        .collect();
+                    Box::new(self.compile(rows, splice(&columns, col, std::iter::empty())))
+                });
+                Decision::Switch {
+                    occurrence,
+                    cases,
+                    default,
+                }
+            }
+            Type::Unit => unreachable!("refutable pattern of type Unit"),
+        }
+    }
+}
+
+impl Compiler<'_> {
+    /// An occurrence within the value at `occurrence` whose type is a sum
+    /// type without variants, if any. Such values do not exist.
+    fn empty_occurrence(&self, occurrence: &Occurrence, r#type: &Type) -> Option<Occurrence> {
+        match r#type {
+            Type::Unit => None,
+            Type::Tuple(types) => types.iter().enumerate().find_map(|(index, r#type)| {
+                self.empty_occurrence(&occurrence.field(index), r#type)
+            }),
+            Type::Named(sum) => self
+                .data_types
+                .variants(sum)
+                .expect("sum types are checked before compilation")
+                .is_empty()
+                .then(|| occurrence.clone()),
+        }
+    }
+}
+
+fn splice(
+    columns: &[(Occurrence, Type)],
+    col: usize,
+    replacement: impl Iterator<Item = (Occurrence, Type)>,
+) -> Vec<(Occurrence, Type)> {
+    let mut columns = columns.to_vec();
+    columns.splice(col..col + 1, replacement);
+    columns
+}
+
+fn collect_arms(decision: &Decision, arms: &mut HashSet<usize>) {
+    match decision {
+        Decision::Fail => {}
+        Decision::Leaf { arm, .. } => {
+            arms.insert(*arm);
+        }
+        Decision::Switch { cases, default, .. } => {
+            for (_, decision) in cases {
+                collect_arms(decision, arms);
+            }
+            if let Some(default) = default {
+                collect_arms(default, arms);
+            }
+        }
+    }
+}
+
+/// What is known about the value at an occurrence on a path through a
+/// decision tree.
+#[derive(Clone)]
+enum Constraint {
+    Is(String),
+    IsNoneOf(Vec<String>),
+}
+
+/// Find a path to a `Fail` node in the decision tree.
+fn find_fail(
+    decision: &Decision,
+    path: &mut Vec<(Occurrence, Constraint)>,
+) -> Option<Vec<(Occurrence, Constraint)>> {
+    match decision {
+        Decision::Fail => Some(path.clone()),
+        Decision::Leaf { .. } => None,
+        Decision::Switch {
+            occurrence,
+            cases,
+            default,
+        } => {
+            for (variant, decision) in cases {
+                path.push((occurrence.clone(), Constraint::Is(variant.clone())));
+                let res = find_fail(decision, path);
+                path.pop();
+                if res.is_some() {
+                    return res;
+                }
+            }
+            let default = default.as_ref()?;
+            let others = cases.iter().map(|(variant, _)| variant.clone()).collect();
+            path.push((occurrence.clone(), Constraint::IsNoneOf(others)));
+            let res = find_fail(default, path);
+            path.pop();
+            res
+        }
+    }
+}
+
+/// Construction of an example value satisfying all constraints on a path.
+struct Witness<'a> {
+    data_types: &'a DataTypes,
+    path: &'a [(Occurrence, Constraint)],
+}
+
+impl Witness<'_> {
+    fn example(&self, occurrence: &Occurrence, r#type: &Type) -> Pattern {
+        if !self
+            .path
+            .iter()
+            .any(|(constrained, _)| occurrence.is_prefix_of(constrained))
+        {
+            return Pattern::Wildcard;
+        }
+        match r#type {
+            Type::Tuple(types) => Pattern::Tuple(
+                types
+                    .iter()
+                    .enumerate()
+                    .map(|(index, r#type)| self.example(&occurrence.field(index), r#type))
+                    .collect(),
+            ),
+            Type::Named(sum) => {
+                let variants = self
+                    .data_types
+                    .variants(sum)
+                    .expect("sum types are checked before compilation");
+                let constraint = self
+                    .path
+                    .iter()
+                    .find(|(constrained, _)| constrained == occurrence)
+                    .map(|(_, constraint)| constraint)
+                    .expect("fields are only constrained after their parent");
+                let variant = match constraint {
+                    Constraint::Is(name) => variants
+                        .iter()
+                        .find(|variant| variant.name == *name)
+                        .expect("variant should exist"),
+                    Constraint::IsNoneOf(names) => variants
+                        .iter()
+                        .find(|variant| !names.contains(&variant.name))
+                        .expect("default branch should have a variant"),
+                };
+                let args = variant
+                    .fields
+                    .iter()
+                    .enumerate()
+                    .map(|(index, r#type)| self.example(&occurrence.field(index), r#type))
+                    .collect();
+                Pattern::Variant(variant.name.clone(), args)
+            }
+            Type::Unit => Pattern::Wildcard,
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::fmt::Display;
+
+/// Position of a sub-value within the scrutinee, as the sequence of field
+/// indices leading to it.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Occurrence(pub Vec<usize>);
+
+impl Occurrence {
+    pub fn field(&self, index: usize) -> Self {
+        let mut path = self.0.clone();
+        path.push(index);
+        Self(path)
+    }
+
+    pub fn is_prefix_of(&self, other: &Occurrence) -> bool {
+        other.0.starts_with(&self.0)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Decision {
+    /// No arm matches. Only reachable in non-exhaustive matches.
+    Fail,
+    /// Select the arm with the given index after binding its variables.
+    Leaf {
+        arm: usize,
+        bindings: Vec<(String, Occurrence)>,
+    },
+    /// Branch on the variant of the sum value at `occurrence`. Variants
+    /// without a case of their own go to `default`.
+    Switch {
+        occurrence: Occurrence,
+        cases: Vec<(String, Decision)>,
+        default: Option<Box<Decision>>,
+    },
+}
+
+impl Display for Occurrence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "$")?;
+        for index in &self.0 {
+            write!(f, ".{}", index)?;
+        }
+        Ok(())
+    }
+}
+
+const INDENT: &str = "    ";
+
+impl Decision {
+    fn render(&self, indent: usize, out: &mut String) {
+        match self {
+            Self::Fail => out.push_str("fail"),
+            Self::Leaf { arm, bindings } => {
+                out.push_str(&format!("arm {}", arm));
+                if !bindings.is_empty() {
+                    let bindings: Vec<_> = bindings
+                        .iter()
+                        .map(|(name, occurrence)| format!("{} = {}", name, occurrence))
+                        .collect();
+                    out.push_str(&format!(" [{}]", bindings.join(", ")));
+                }
+            }
+            Self::Switch {
+                occurrence,
+                cases,
+                default,
+            } => {
+                let prefix = INDENT.repeat(indent + 1);
+                out.push_str(&format!("switch {} {{\n", occurrence));
+                for (variant, decision) in cases {
+                    out.push_str(&format!("{}{} => ", prefix, variant));
+                    decision.render(indent + 1, out);
+                    out.push('\n');
+                }
+                if let Some(default) = default {
+                    out.push_str(&format!("{}_ => ", prefix));
+                    default.render(indent + 1, out);
+                    out.push('\n');
+                }
+                out.push_str(&format!("{}}}", INDENT.repeat(indent)));
+            }
+        }
+    }
+}
+
+impl Display for Decision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut out = String::new();
+        self.render(0, &mut out);
+        write!(f, "{}", out)
+    }
+}
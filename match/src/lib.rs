@@ -0,0 +1,146 @@
+//! Compilation of nested patterns into decision trees, following Maranget's
+//! "Compiling Pattern Matching to Good Decision Trees". The compiler also
+//! reports non-exhaustive matches and redundant arms.
+//!
+//! NOTE: The surface language has no sums, products or `case` yet. The types
+//! and patterns below are the compiler's own and will be produced from the
+//! AST once it does.
+use std::{collections::HashMap, fmt::Display};
+
+use felix_common::SrcSpan;
+
+mod compiler;
+mod decision;
+
+pub use compiler::{compile, MatchResult};
+pub use decision::{Decision, Occurrence};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Type {
+    Unit,
+    Tuple(Vec<Type>),
+    /// A sum type declared in `DataTypes`. Sum types may be recursive.
+    Named(String),
+}
+
+#[derive(Clone, Debug)]
+pub struct Variant {
+    pub name: String,
+    pub fields: Vec<Type>,
+}
+
+/// The declarations of all sum types, mapping their names to their
+/// variants.
+#[derive(Clone, Debug, Default)]
+pub struct DataTypes {
+    sums: HashMap<String, Vec<Variant>>,
+}
+
+impl DataTypes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn declare(&mut self, name: &str, variants: Vec<Variant>) {
+        self.sums.insert(String::from(name), variants);
+    }
+
+    /// The variants of the sum type with the given name. Returns `None` if
+    /// the type is not declared.
+    pub fn variants(&self, name: &str) -> Option<&[Variant]> {
+        self.sums.get(name).map(Vec::as_slice)
+    }
+}
+
+pub fn variant(name: &str, fields: Vec<Type>) -> Variant {
+    Variant {
+        name: String::from(name),
+        fields,
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Pattern {
+    Wildcard,
+    Var(String),
+    Unit,
+    Tuple(Vec<Pattern>),
+    Variant(String, Vec<Pattern>),
+}
+
+pub fn pvar(name: &str) -> Pattern {
+    Pattern::Var(String::from(name))
+}
+
+pub fn pvariant(name: &str, args: Vec<Pattern>) -> Pattern {
+    Pattern::Variant(String::from(name), args)
+}
+
+/// An arm of a match. The body of the arm is irrelevant to the compiler and
+/// referred to by the arm's index.
+#[derive(Clone, Debug)]
+pub struct Arm {
+    pub pattern: Pattern,
+    pub span: SrcSpan<u32>,
+}
+
+/// A match expression on a scrutinee of the given type.
+#[derive(Clone, Debug)]
+pub struct Match {
+    pub scrutinee: Type,
+    pub arms: Vec<Arm>,
+    pub span: SrcSpan<u32>,
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unit => write!(f, "Unit"),
+            Self::Tuple(types) => {
+                write!(f, "(")?;
+                for (index, r#type) in types.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", r#type)?;
+                }
+                write!(f, ")")
+            }
+            Self::Named(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Wildcard => write!(f, "_"),
+            Self::Var(name) => write!(f, "{}", name),
+            Self::Unit => write!(f, "unit"),
+            Self::Tuple(elems) => {
+                write!(f, "(")?;
+                fmt_list(f, elems)?;
+                write!(f, ")")
+            }
+            Self::Variant(name, args) if args.is_empty() => write!(f, "{}", name),
+            Self::Variant(name, args) => {
+                write!(f, "{}(", name)?;
+                fmt_list(f, args)?;
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+fn fmt_list(f: &mut std::fmt::Formatter<'_>, patterns: &[Pattern]) -> std::fmt::Result {
+    for (index, pattern) in patterns.iter().enumerate() {
+        if index > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", pattern)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
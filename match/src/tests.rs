@@ -0,0 +1,411 @@
+use insta::assert_snapshot;
+
+use felix_common::{explain, srcloc::Mapper, Code, Severity, SrcSpan};
+
+use super::*;
+
+fn data_types() -> DataTypes {
+    let mut data_types = DataTypes::new();
+    data_types.declare(
+        "Bool",
+        vec![variant("True", vec![]), variant("False", vec![])],
+    );
+    data_types.declare(
+        "List",
+        vec![
+            variant("Nil", vec![]),
+            variant("Cons", vec![bool(), list()]),
+        ],
+    );
+    data_types.declare(
+        "Option",
+        vec![variant("None", vec![]), variant("Some", vec![list()])],
+    );
+    data_types.declare("Void", vec![]);
+    data_types
+}
+
+fn bool() -> Type {
+    Type::Named(String::from("Bool"))
+}
+
+fn list() -> Type {
+    Type::Named(String::from("List"))
+}
+
+fn option() -> Type {
+    Type::Named(String::from("Option"))
+}
+
+fn void() -> Type {
+    Type::Named(String::from("Void"))
+}
+
+fn ptrue() -> Pattern {
+    pvariant("True", vec![])
+}
+
+fn pfalse() -> Pattern {
+    pvariant("False", vec![])
+}
+
+fn pnil() -> Pattern {
+    pvariant("Nil", vec![])
+}
+
+fn pcons(head: Pattern, tail: Pattern) -> Pattern {
+    pvariant("Cons", vec![head, tail])
+}
+
+/// Compile a match with one arm per line of the source and render the
+/// decision tree followed by the problems.
+fn compile_arms(scrutinee: Type, patterns: Vec<Pattern>) -> String {
+    let lines: Vec<String> = patterns
+        .iter()
+        .map(|pattern| format!("| {}", pattern))
+        .collect();
+    let source = lines.join("\n");
+    let mut start = 0;
+    let arms = patterns
+        .into_iter()
+        .zip(&lines)
+        .map(|(pattern, line)| {
+            let span = SrcSpan {
+                start,
+                end: start + line.len() as u32,
+            };
+            start = span.end + 1;
+            Arm { pattern, span }
+        })
+        .collect();
+    let r#match = Match {
+        scrutinee,
+        arms,
+        span: SrcSpan {
+            start: 0,
+            end: source.len() as u32,
+        },
+    };
+    let mapper = Mapper::new(&source);
+    let result = compile(&data_types(), &r#match, &mapper);
+    let mut out = result.decision.to_string();
+    for problem in result.problems {
+        out.push_str(&format!(
+            "\n{}:{}-{}:{}: {}[{}]: {}",
+            problem.start.line,
+            problem.start.column,
+            problem.end.line,
+            problem.end.column,
+            problem.severity,
+            problem.code,
+            problem.message
+        ));
+    }
+    out
+}
+
+#[test]
+fn match_bool_exhaustive() {
+    assert_snapshot!(compile_arms(bool(), vec![ptrue(), pfalse()]), @r"
+    switch $ {
+        True => arm 0
+        False => arm 1
+    }
+    ");
+}
+
+#[test]
+fn match_bool_default() {
+    assert_snapshot!(compile_arms(bool(), vec![pfalse(), pvar("b")]), @r"
+    switch $ {
+        False => arm 0
+        _ => arm 1 [b = $]
+    }
+    ");
+}
+
+#[test]
+fn match_bool_non_exhaustive() {
+    assert_snapshot!(compile_arms(bool(), vec![ptrue()]), @r"
+    switch $ {
+        True => arm 0
+        _ => fail
+    }
+    0:0-0:6: error[M0003]: Non-exhaustive match. For example, False is not matched.
+    ");
+}
+
+#[test]
+fn match_wildcard_first_is_redundant() {
+    assert_snapshot!(compile_arms(bool(), vec![Pattern::Wildcard, ptrue()]), @r"
+    arm 0
+    1:0-1:6: warning[M0002]: Redundant arm. The pattern True only matches values matched by earlier arms.
+    ");
+}
+
+#[test]
+fn match_tuple() {
+    let patterns = vec![
+        Pattern::Tuple(vec![ptrue(), Pattern::Wildcard]),
+        Pattern::Tuple(vec![Pattern::Wildcard, ptrue()]),
+    ];
+    assert_snapshot!(compile_arms(Type::Tuple(vec![bool(), bool()]), patterns), @r"
+    switch $.0 {
+        True => arm 0
+        _ => switch $.1 {
+            True => arm 1
+            _ => fail
+        }
+    }
+    0:0-1:11: error[M0003]: Non-exhaustive match. For example, (False, False) is not matched.
+    ");
+}
+
+#[test]
+fn match_tuple_redundant() {
+    let patterns = vec![
+        Pattern::Tuple(vec![ptrue(), Pattern::Wildcard]),
+        Pattern::Tuple(vec![Pattern::Wildcard, pfalse()]),
+        Pattern::Tuple(vec![pfalse(), pvar("x")]),
+        Pattern::Tuple(vec![ptrue(), ptrue()]),
+    ];
+    assert_snapshot!(compile_arms(Type::Tuple(vec![bool(), bool()]), patterns), @r"
+    switch $.0 {
+        True => arm 0
+        False => switch $.1 {
+            False => arm 1
+            _ => arm 2 [x = $.1]
+        }
+    }
+    3:0-3:14: warning[M0002]: Redundant arm. The pattern (True, True) only matches values matched by earlier arms.
+    ");
+}
+
+#[test]
+fn match_list_nested() {
+    let patterns = vec![
+        pnil(),
+        pcons(pvar("x"), pnil()),
+        pcons(ptrue(), pcons(Pattern::Wildcard, pvar("rest"))),
+    ];
+    assert_snapshot!(compile_arms(list(), patterns), @r"
+    switch $ {
+        Nil => arm 0
+        Cons => switch $.1 {
+            Nil => arm 1 [x = $.0]
+            Cons => switch $.0 {
+                True => arm 2 [rest = $.1.1]
+                _ => fail
+            }
+        }
+    }
+    0:0-2:27: error[M0003]: Non-exhaustive match. For example, Cons(False, Cons(_, _)) is not matched.
+    ");
+}
+
+#[test]
+fn match_option_of_list() {
+    let patterns = vec![
+        pvariant("None", vec![]),
+        pvariant("Some", vec![pnil()]),
+        pvariant("Some", vec![pcons(pfalse(), pvar("xs"))]),
+    ];
+    assert_snapshot!(compile_arms(option(), patterns), @r"
+    switch $ {
+        None => arm 0
+        Some => switch $.0 {
+            Nil => arm 1
+            Cons => switch $.0.0 {
+                False => arm 2 [xs = $.0.1]
+                _ => fail
+            }
+        }
+    }
+    0:0-2:23: error[M0003]: Non-exhaustive match. For example, Some(Cons(True, _)) is not matched.
+    ");
+}
+
+#[test]
+fn match_unit() {
+    let patterns = vec![Pattern::Tuple(vec![Pattern::Unit, pvar("b")])];
+    assert_snapshot!(compile_arms(Type::Tuple(vec![Type::Unit, bool()]), patterns), @"arm 0 [b = $.1]");
+}
+
+#[test]
+fn match_redundant_arm_suggestion() {
+    let source = "| True\n| _\n| False";
+    let arm = |pattern, start, end| Arm {
+        pattern,
+        span: SrcSpan { start, end },
+    };
+    let r#match = Match {
+        scrutinee: bool(),
+        arms: vec![
+            arm(ptrue(), 0, 6),
+            arm(pvar("_"), 7, 10),
+            arm(pfalse(), 11, 18),
+        ],
+        span: SrcSpan { start: 0, end: 18 },
+    };
+    let mapper = Mapper::new(source);
+    let result = compile(&data_types(), &r#match, &mapper);
+    let [problem] = result.problems.as_slice() else {
+        panic!("expected one problem, got {:?}", result.problems);
+    };
+    assert_eq!(problem.severity, Severity::WARNING);
+    let [suggestion] = problem.suggestions.as_slice() else {
+        panic!("expected one suggestion, got {:?}", problem.suggestions);
+    };
+    let mut fixed = String::from(source);
+    for edit in &suggestion.edits {
+        fixed.replace_range(edit.span.into_range(), &edit.text);
+    }
+    assert_eq!(fixed, "| True\n| _\n");
+}
+
+#[test]
+fn match_ill_typed() {
+    let patterns = vec![
+        pvariant("Maybe", vec![]),
+        pcons(ptrue(), pnil()),
+        pvariant("Nil", vec![ptrue()]),
+        Pattern::Tuple(vec![Pattern::Unit]),
+    ];
+    assert_snapshot!(compile_arms(list(), patterns), @r"
+    fail
+    0:0-0:7: error[M0001]: Type List has no variant Maybe.
+    2:0-2:11: error[M0001]: Variant Nil has 0 fields, but the pattern has 1.
+    3:0-3:8: error[M0001]: Found pattern (unit), expected a pattern of type List.
+    ");
+}
+
+#[test]
+fn match_undeclared_type() {
+    let scrutinee = Type::Named(String::from("Tree"));
+    assert_snapshot!(compile_arms(scrutinee, vec![Pattern::Wildcard]), @r"
+    fail
+    0:0-0:3: error[M0001]: Type Tree is not declared.
+    ");
+    let scrutinee = Type::Tuple(vec![bool(), Type::Named(String::from("Tree"))]);
+    assert_snapshot!(compile_arms(scrutinee, vec![pvar("x")]), @r"
+    fail
+    0:0-0:3: error[M0001]: Type Tree is not declared.
+    ");
+}
+
+#[test]
+fn match_empty_type() {
+    assert_snapshot!(compile_arms(void(), vec![]), @r"
+    switch $ {
+    }
+    ");
+    let scrutinee = Type::Tuple(vec![bool(), void()]);
+    assert_snapshot!(compile_arms(scrutinee, vec![]), @r"
+    switch $.1 {
+    }
+    ");
+    assert_snapshot!(compile_arms(void(), vec![pvar("x")]), @r"
+    arm 0 [x = $]
+    ");
+    assert_snapshot!(compile_arms(bool(), vec![]), @r"
+    fail
+    0:0-0:0: error[M0003]: Non-exhaustive match. For example, _ is not matched.
+    ");
+}
+
+/// A reader of matches in the syntax of the explanations of the problem
+/// codes, e.g. `match List` followed by arms like `| Cons(x, Nil)`.
+struct Reader<'a> {
+    tokens: std::iter::Peekable<std::vec::IntoIter<&'a str>>,
+}
+
+impl<'a> Reader<'a> {
+    fn new(source: &'a str) -> Self {
+        let mut tokens = Vec::new();
+        let mut rest = source.trim_start();
+        while let Some(char) = rest.chars().next() {
+            let len = if char.is_ascii_alphanumeric() || char == '_' {
+                rest.find(|char: char| !char.is_ascii_alphanumeric() && char != '_')
+                    .unwrap_or(rest.len())
+            } else {
+                char.len_utf8()
+            };
+            tokens.push(&rest[..len]);
+            rest = rest[len..].trim_start();
+        }
+        Self {
+            tokens: tokens.into_iter().peekable(),
+        }
+    }
+
+    fn expect(&mut self, token: &str) {
+        assert_eq!(self.tokens.next(), Some(token));
+    }
+
+    /// Read items up to the closing parenthesis, separated by commas.
+    fn list<T>(&mut self, item: impl Fn(&mut Self) -> T) -> Vec<T> {
+        let mut items = vec![item(self)];
+        while self.tokens.next_if_eq(&",").is_some() {
+            items.push(item(self));
+        }
+        self.expect(")");
+        items
+    }
+
+    fn r#type(&mut self) -> Type {
+        match self.tokens.next().expect("type") {
+            "Unit" => Type::Unit,
+            "(" => Type::Tuple(self.list(Self::r#type)),
+            name => Type::Named(String::from(name)),
+        }
+    }
+
+    fn pattern(&mut self) -> Pattern {
+        match self.tokens.next().expect("pattern") {
+            "_" => Pattern::Wildcard,
+            "unit" => Pattern::Unit,
+            "(" => Pattern::Tuple(self.list(Self::pattern)),
+            name if name.starts_with(char::is_uppercase) => {
+                let args = match self.tokens.next_if_eq(&"(") {
+                    Some(_) => self.list(Self::pattern),
+                    None => Vec::new(),
+                };
+                pvariant(name, args)
+            }
+            name => pvar(name),
+        }
+    }
+
+    fn r#match(mut self) -> (Type, Vec<Pattern>) {
+        self.expect("match");
+        let scrutinee = self.r#type();
+        let mut patterns = Vec::new();
+        while self.tokens.next_if_eq(&"|").is_some() {
+            patterns.push(self.pattern());
+        }
+        assert_eq!(self.tokens.next(), None);
+        (scrutinee, patterns)
+    }
+}
+
+#[test]
+fn explanation_examples() {
+    for code in Code::ALL
+        .into_iter()
+        .filter(|code| code.as_str().starts_with('M'))
+    {
+        let examples = explain::examples(code);
+        assert!(!examples.is_empty(), "code {}", code);
+        for example in examples {
+            assert_eq!(example.language, explain::Language::Match);
+            let (scrutinee, patterns) = Reader::new(example.source).r#match();
+            let out = compile_arms(scrutinee, patterns);
+            let reported = out.contains(&format!("[{}]", code));
+            assert_eq!(
+                reported, example.fails,
+                "code {}, example {:?}, result {}",
+                code, example.source, out
+            );
+        }
+    }
+}
@@ -0,0 +1,297 @@
+//! Simplifier for the AST. All rewrites rely on the language being pure and
+//! well-typed programs being terminating, which makes it safe to drop, move
+//! and duplicate the evaluation of expressions.
+use std::{collections::HashSet, rc::Rc};
+
+use felix_type_checker::ast::{self, Binder, Expr, FromExpr, Ident};
+
+#[derive(Debug)]
+pub enum OptError {
+    BrokenNode(Rc<ast::Broken>),
+    UnsupportedNode(Expr),
+}
+
+pub type Result<T> = std::result::Result<T, OptError>;
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// Bindings of values whose size does not exceed this threshold are
+    /// inlined at all their uses.
+    pub inline_size: usize,
+    /// Maximum number of simplification rounds.
+    pub max_rounds: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            inline_size: 8,
+            max_rounds: 16,
+        }
+    }
+}
+
+/// Simplify an expression with the default configuration.
+pub fn optimize(expr: &Expr) -> Result<Expr> {
+    optimize_with(&Config::default(), expr)
+}
+
+/// Simplify an expression by repeatedly applying the following rewrites
+/// until none of them applies anymore or `config.max_rounds` is exhausted:
+///
+/// 1. `(λx:T. e1) e2` becomes `let x = e2 in e1`.
+/// 2. `let x = e1 in e2` becomes `e2` if `x` does not occur in `e2`.
+/// 3. `let x = e1 in e2` becomes `e2[e1/x]` if `e1` is a value which is
+///    small or used only once, or if `e1` is used only once and not under a
+///    λ, where it could be evaluated more than once.
+///
+/// Since substitution preserves types, so does the simplifier. Renamed
+/// binders get names of the form `x%n`.
+pub fn optimize_with(config: &Config, expr: &Expr) -> Result<Expr> {
+    let mut simplifier = Simplifier {
+        config,
+        counter: 0,
+        taken: all_names(expr),
+        changed: false,
+    };
+    let mut expr = expr.clone();
+    for _ in 0..config.max_rounds {
+        simplifier.changed = false;
+        expr = simplifier.simplify(&expr)?;
+        if !simplifier.changed {
+            break;
+        }
+    }
+    Ok(expr)
+}
+
+/// Number of nodes in an expression.
+pub fn size(expr: &Expr) -> usize {
+    if let Some(abs) = ast::Abs::from_expr(expr) {
+        1 + size(&abs.body)
+    } else if let Some(app) = ast::App::from_expr(expr) {
+        1 + size(&app.fun) + size(&app.arg)
+    } else if let Some(let_) = ast::Let::from_expr(expr) {
+        1 + size(&let_.bindee) + size(&let_.body)
+    } else {
+        1
+    }
+}
+
+struct Simplifier<'a> {
+    config: &'a Config,
+    counter: u32,
+    /// Names which must not be used for renamed binders.
+    taken: HashSet<String>,
+    changed: bool,
+}
+
+impl Simplifier<'_> {
+    fn simplify(&mut self, expr: &Expr) -> Result<Expr> {
+        if ast::Var::from_expr(expr).is_some() || ast::Unit::from_expr(expr).is_some() {
+            Ok(expr.clone())
+        } else if let Some(abs) = ast::Abs::from_expr(expr) {
+            Ok(ast::abs(abs.binder.clone(), self.simplify(&abs.body)?))
+        } else if let Some(app) = ast::App::from_expr(expr) {
+            let fun = self.simplify(&app.fun)?;
+            let arg = self.simplify(&app.arg)?;
+            match ast::Abs::from_expr(&fun) {
+                Some(abs) => {
+                    self.changed = true;
+                    // NOTE: The type checker rejects annotations on `let`.
+                    let binder = ast::binder(abs.binder.name.as_str());
+                    Ok(ast::let_(binder, arg, abs.body.clone()))
+                }
+                None => Ok(ast::app(fun, arg)),
+            }
+        } else if let Some(let_) = ast::Let::from_expr(expr) {
+            let bindee = self.simplify(&let_.bindee)?;
+            let body = self.simplify(&let_.body)?;
+            let uses = Uses::count(&let_.binder.name, &body);
+            if uses.total == 0 {
+                self.changed = true;
+                Ok(body)
+            } else if self.should_inline(&bindee, &uses) {
+                self.changed = true;
+                let free = free_vars(&bindee);
+                Ok(self.subst(&body, &let_.binder.name, &bindee, &free))
+            } else {
+                Ok(ast::let_(let_.binder.clone(), bindee, body))
+            }
+        } else if let Some(broken) = ast::Broken::from_expr(expr) {
+            Err(OptError::BrokenNode(broken))
+        } else {
+            Err(OptError::UnsupportedNode(expr.clone()))
+        }
+    }
+
+    fn should_inline(&self, bindee: &Expr, uses: &Uses) -> bool {
+        if is_value(bindee) {
+            uses.total == 1 || size(bindee) <= self.config.inline_size
+        } else {
+            uses.total == 1 && uses.under_abs == 0
+        }
+    }
+
+    fn fresh(&mut self, base: &Ident) -> Ident {
+        loop {
+            self.counter += 1;
+            let name = format!("{}%{}", base.as_str(), self.counter);
+            if self.taken.insert(name.clone()) {
+                return ast::ident(&name);
+            }
+        }
+    }
+
+    /// Rename `binder` if it would capture a free variable of the expression
+    /// being substituted and return the body to continue substituting in.
+    fn avoid_capture(
+        &mut self,
+        binder: &Binder,
+        body: &Expr,
+        free: &HashSet<String>,
+    ) -> (Binder, Expr) {
+        if free.contains(binder.name.as_str()) {
+            let name = self.fresh(&binder.name);
+            let body = self.subst(
+                body,
+                &binder.name,
+                &ast::var(name.as_str()),
+                &HashSet::new(),
+            );
+            let binder = Binder {
+                name,
+                annot: binder.annot.clone(),
+            };
+            (binder, body)
+        } else {
+            (binder.clone(), body.clone())
+        }
+    }
+
+    /// Capture-avoiding substitution of `value` for `x` in `expr`, where
+    /// `free` are the free variables of `value`.
+    fn subst(&mut self, expr: &Expr, x: &Ident, value: &Expr, free: &HashSet<String>) -> Expr {
+        if let Some(var) = ast::Var::from_expr(expr) {
+            if var.name == *x {
+                value.clone()
+            } else {
+                expr.clone()
+            }
+        } else if let Some(abs) = ast::Abs::from_expr(expr) {
+            if abs.binder.name == *x {
+                return expr.clone();
+            }
+            let (binder, body) = self.avoid_capture(&abs.binder, &abs.body, free);
+            ast::abs(binder, self.subst(&body, x, value, free))
+        } else if let Some(app) = ast::App::from_expr(expr) {
+            ast::app(
+                self.subst(&app.fun, x, value, free),
+                self.subst(&app.arg, x, value, free),
+            )
+        } else if let Some(let_) = ast::Let::from_expr(expr) {
+            let bindee = self.subst(&let_.bindee, x, value, free);
+            if let_.binder.name == *x {
+                return ast::let_(let_.binder.clone(), bindee, let_.body.clone());
+            }
+            let (binder, body) = self.avoid_capture(&let_.binder, &let_.body, free);
+            ast::let_(binder, bindee, self.subst(&body, x, value, free))
+        } else {
+            expr.clone()
+        }
+    }
+}
+
+fn is_value(expr: &Expr) -> bool {
+    ast::Var::from_expr(expr).is_some()
+        || ast::Abs::from_expr(expr).is_some()
+        || ast::Unit::from_expr(expr).is_some()
+}
+
+/// Occurrences of a variable in an expression.
+#[derive(Default)]
+struct Uses {
+    total: usize,
+    /// Occurrences under a λ, which might be evaluated more than once.
+    under_abs: usize,
+}
+
+impl Uses {
+    fn count(x: &Ident, expr: &Expr) -> Self {
+        let mut uses = Self::default();
+        uses.visit(x, expr, false);
+        uses
+    }
+
+    fn visit(&mut self, x: &Ident, expr: &Expr, under_abs: bool) {
+        if let Some(var) = ast::Var::from_expr(expr) {
+            if var.name == *x {
+                self.total += 1;
+                if under_abs {
+                    self.under_abs += 1;
+                }
+            }
+        } else if let Some(abs) = ast::Abs::from_expr(expr) {
+            if abs.binder.name != *x {
+                self.visit(x, &abs.body, true);
+            }
+        } else if let Some(app) = ast::App::from_expr(expr) {
+            self.visit(x, &app.fun, under_abs);
+            self.visit(x, &app.arg, under_abs);
+        } else if let Some(let_) = ast::Let::from_expr(expr) {
+            self.visit(x, &let_.bindee, under_abs);
+            if let_.binder.name != *x {
+                self.visit(x, &let_.body, under_abs);
+            }
+        }
+    }
+}
+
+fn all_names(expr: &Expr) -> HashSet<String> {
+    fn go(expr: &Expr, names: &mut HashSet<String>) {
+        if let Some(var) = ast::Var::from_expr(expr) {
+            names.insert(String::from(var.name.as_str()));
+        } else if let Some(abs) = ast::Abs::from_expr(expr) {
+            names.insert(String::from(abs.binder.name.as_str()));
+            go(&abs.body, names);
+        } else if let Some(app) = ast::App::from_expr(expr) {
+            go(&app.fun, names);
+            go(&app.arg, names);
+        } else if let Some(let_) = ast::Let::from_expr(expr) {
+            names.insert(String::from(let_.binder.name.as_str()));
+            go(&let_.bindee, names);
+            go(&let_.body, names);
+        }
+    }
+    let mut names = HashSet::new();
+    go(expr, &mut names);
+    names
+}
+
+fn free_vars(expr: &Expr) -> HashSet<String> {
+    fn go(expr: &Expr, bound: &mut Vec<Ident>, free: &mut HashSet<String>) {
+        if let Some(var) = ast::Var::from_expr(expr) {
+            if !bound.contains(&var.name) {
+                free.insert(String::from(var.name.as_str()));
+            }
+        } else if let Some(abs) = ast::Abs::from_expr(expr) {
+            bound.push(abs.binder.name.clone());
+            go(&abs.body, bound, free);
+            bound.pop();
+        } else if let Some(app) = ast::App::from_expr(expr) {
+            go(&app.fun, bound, free);
+            go(&app.arg, bound, free);
+        } else if let Some(let_) = ast::Let::from_expr(expr) {
+            go(&let_.bindee, bound, free);
+            bound.push(let_.binder.name.clone());
+            go(&let_.body, bound, free);
+            bound.pop();
+        }
+    }
+    let mut free = HashSet::new();
+    go(expr, &mut Vec::new(), &mut free);
+    free
+}
+
+#[cfg(test)]
+mod tests;
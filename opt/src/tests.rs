@@ -0,0 +1,212 @@
+use assert_matches::assert_matches;
+use insta::assert_snapshot;
+
+use felix_interpreter::Value;
+use felix_type_checker::{ast::*, stlc, Checker, Context, Type};
+
+use super::*;
+
+fn eval(expr: &Expr) -> felix_interpreter::Result<Value> {
+    felix_interpreter::eval(&felix_core::erase(expr).unwrap())
+}
+
+fn unit_to_unit() -> Type {
+    Type::Arrow(Type::Unit.into(), Type::Unit.into())
+}
+
+fn id_unit() -> Expr {
+    abs(binder_annot("u", Type::Unit), var("u"))
+}
+
+fn twice() -> Expr {
+    let_(
+        binder("twice"),
+        abs(
+            binder_annot("f", unit_to_unit()),
+            abs(
+                binder_annot("u", Type::Unit),
+                app(var("f"), app(var("f"), var("u"))),
+            ),
+        ),
+        app(app(var("twice"), id_unit()), unit()),
+    )
+}
+
+/// A function which is too big to be inlined at its two uses.
+fn big() -> Expr {
+    let mut body = var("u");
+    for _ in 0..8 {
+        body = app(var("f"), body);
+    }
+    abs(
+        binder_annot("f", unit_to_unit()),
+        abs(binder_annot("u", Type::Unit), body),
+    )
+}
+
+/// Inlining `y` into the body of the inner `λx` must rename the inner `λx`.
+fn capture() -> Expr {
+    abs(
+        binder_annot("x", unit_to_unit()),
+        let_(
+            binder("y"),
+            var("x"),
+            abs(
+                binder_annot("x", Type::Unit),
+                app(var("g"), app(var("y"), var("x"))),
+            ),
+        ),
+    )
+}
+
+fn corpus() -> Vec<(&'static str, Expr)> {
+    vec![
+        ("unit", unit()),
+        ("beta", app(id_unit(), unit())),
+        (
+            "dead_let",
+            let_(binder("x"), app(id_unit(), unit()), unit()),
+        ),
+        (
+            "shared",
+            let_(
+                binder("x"),
+                app(id_unit(), unit()),
+                app(
+                    app(abs(binder_annot("a", Type::Unit), id_unit()), var("x")),
+                    var("x"),
+                ),
+            ),
+        ),
+        ("twice", twice()),
+        (
+            "big",
+            let_(
+                binder("big"),
+                big(),
+                app(app(var("big"), app(var("big"), id_unit())), unit()),
+            ),
+        ),
+        (
+            "shadowing",
+            let_(
+                binder("x"),
+                unit(),
+                let_(binder("f"), abs(binder_annot("y", Type::Unit), var("x")), {
+                    let_(binder("x"), id_unit(), app(var("f"), app(var("x"), unit())))
+                }),
+            ),
+        ),
+    ]
+}
+
+#[test]
+fn optimize_preserves_types() {
+    let checker = stlc::get();
+    for (name, expr) in corpus() {
+        let expected = checker
+            .infer(&Context::new(), &expr)
+            .unwrap_or_else(|error| panic!("{}: {:?}", name, error));
+        let optimized = optimize(&expr).unwrap();
+        let actual = checker
+            .infer(&Context::new(), &optimized)
+            .unwrap_or_else(|error| panic!("{}: {:?} in {}", name, error, optimized));
+        assert_eq!(actual, expected, "{}", name);
+    }
+}
+
+#[test]
+fn optimize_passes_lint() {
+    let checker = stlc::get();
+    for (name, expr) in corpus() {
+        let optimized = optimize(&expr).unwrap();
+        let core = felix_core::elaborate(checker, &Context::new(), &optimized)
+            .unwrap_or_else(|error| panic!("{}: {:?} in {}", name, error, optimized));
+        felix_core::lint(&core).unwrap_or_else(|error| panic!("{}: {:?} in {}", name, error, core));
+    }
+}
+
+#[test]
+fn optimize_preserves_types_in_context() {
+    let checker = stlc::get();
+    let ctx = Context::new().extend(ident("g"), unit_to_unit());
+    let expected = checker.infer(&ctx, &capture()).unwrap();
+    let actual = checker.infer(&ctx, &optimize(&capture()).unwrap()).unwrap();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn optimize_preserves_results() {
+    for (name, expr) in corpus() {
+        let expected = eval(&expr).unwrap();
+        let actual = eval(&optimize(&expr).unwrap()).unwrap();
+        match (&expected, &actual) {
+            (Value::Unit, Value::Unit) | (Value::Closure(_), Value::Closure(_)) => {}
+            _ => panic!("{}: expected {}, found {}", name, expected, actual),
+        }
+    }
+}
+
+#[test]
+fn optimize_does_not_grow() {
+    for (name, expr) in corpus() {
+        let optimized = optimize(&expr).unwrap();
+        assert!(size(&optimized) <= size(&expr), "{}", name);
+    }
+}
+
+#[test]
+fn optimize_beta() {
+    assert_snapshot!(optimize(&app(id_unit(), unit())).unwrap(), @"unit");
+}
+
+#[test]
+fn optimize_dead_let() {
+    let expr = let_(binder("x"), app(var("f"), unit()), var("y"));
+    assert_snapshot!(optimize(&expr).unwrap(), @"y");
+}
+
+#[test]
+fn optimize_twice() {
+    assert_snapshot!(optimize(&twice()).unwrap(), @"unit");
+}
+
+#[test]
+fn optimize_keeps_big() {
+    let (_, expr) = corpus()
+        .into_iter()
+        .find(|(name, _)| *name == "big")
+        .unwrap();
+    assert_snapshot!(optimize(&expr).unwrap(), @"let big = λf:(Unit -> Unit). λu:Unit. f (f (f (f (f (f (f (f u))))))) in big (big (λu:Unit. u)) unit");
+}
+
+#[test]
+fn optimize_keeps_shared_work() {
+    let expr = let_(
+        binder("x"),
+        app(var("f"), unit()),
+        app(var("g"), abs(binder_annot("u", Type::Unit), var("x"))),
+    );
+    assert_snapshot!(optimize(&expr).unwrap(), @"let x = f unit in g (λu:Unit. x)");
+}
+
+#[test]
+fn optimize_avoids_capture() {
+    assert_snapshot!(optimize(&capture()).unwrap(), @"λx:(Unit -> Unit). λx%1:Unit. g (x x%1)");
+}
+
+#[test]
+fn optimize_small_threshold() {
+    let config = Config {
+        inline_size: 0,
+        ..Config::default()
+    };
+    let expr = let_(binder("f"), id_unit(), app(var("f"), app(var("f"), unit())));
+    assert_snapshot!(optimize_with(&config, &expr).unwrap(), @"let f = λu:Unit. u in f (f unit)");
+}
+
+#[test]
+fn optimize_broken() {
+    let expr = let_(binder("x"), broken(), unit());
+    assert_matches!(optimize(&expr), Err(OptError::BrokenNode(_)));
+}
@@ -13,6 +13,17 @@ impl GreenChild {
     }
 }
 
+pub fn program(expr: GreenChild) -> GreenChild {
+    node(PROGRAM, vec![expr])
+}
+
+pub fn definition(binder: GreenChild, bindee: GreenChild) -> GreenChild {
+    node(
+        DEFINITION,
+        vec![token(KW_LET, "let"), binder, token(EQUALS, "="), bindee],
+    )
+}
+
 pub fn expr_abs(binder: GreenChild, expr: GreenChild) -> GreenChild {
     node(
         EXPR_ABS,
@@ -153,6 +164,21 @@ mod tests {
         "#);
     }
 
+    #[test]
+    fn syntax_definition() {
+        let syntax = definition(binder("x", None), expr_meta("e")).into_syntax();
+        assert_debug_snapshot!(syntax, @r#"
+        DEFINITION@0..6
+          KW_LET@0..3 "let"
+          BINDER@3..4
+            NAME@3..4
+              ID_EXPR@3..4 "x"
+          EQUALS@4..5 "="
+          EXPR_META@5..6
+            ID_EXPR@5..6 "e"
+        "#);
+    }
+
     #[test]
     fn syntax_expr_app() {
         let syntax = expr_app(expr_meta("e1"), expr_meta("e2")).into_syntax();
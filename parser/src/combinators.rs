@@ -1,3 +1,5 @@
+use felix_common::Code;
+
 use crate::parser::{Parser, Result};
 use crate::syntax::{NodeKind, TokenKind, TokenKindSet};
 
@@ -42,8 +44,18 @@ impl<'a> Parser<'a> {
                         if top.right_power > left_power {
                             continue;
                         }
-                        let problem =
-                            self.error(format!("Cannot chain operators {} and {}", top.op, op));
+                        let problem = self
+                            .error(
+                                Code::CHAINED_OPERATORS,
+                                format!(
+                                    "Cannot chain operators {} and {}.",
+                                    top.op.description(),
+                                    op.description()
+                                ),
+                            )
+                            .with_note(String::from(
+                                "Use parentheses to say which operator applies first.",
+                            ));
                         self.push_problem(problem);
                         break NodeKind::ERROR;
                     }
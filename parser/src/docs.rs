@@ -0,0 +1,158 @@
+//! Doc comments, i.e. comments starting with `##`. They document the
+//! variable bound by the `let` right after them.
+use crate::syntax::{Node, NodeKind, SyntaxKind, TokenKind};
+
+fn node_kind(node: &Node) -> Option<NodeKind> {
+    match node.kind() {
+        SyntaxKind::Node(kind) => Some(kind),
+        SyntaxKind::Token(_) => None,
+    }
+}
+
+fn child(node: &Node, kind: NodeKind) -> Option<Node> {
+    node.children().find(|child| node_kind(child) == Some(kind))
+}
+
+/// The name bound by a `BINDER`.
+fn name(binder: &Node) -> Option<String> {
+    Some(child(binder, NodeKind::NAME)?.text().to_string())
+}
+
+/// The documentation of the variable bound by the `BINDER` of an
+/// `EXPR_LET` or a `DEFINITION`. It consists of the doc comments in front
+/// of the `let`, without their `##`, one per line. Returns `None` if there
+/// are none or the binder does not belong to a `let`.
+pub fn docs(binder: &Node) -> Option<String> {
+    let parent = binder.parent()?;
+    if !matches!(
+        node_kind(&parent),
+        Some(NodeKind::EXPR_LET | NodeKind::DEFINITION)
+    ) {
+        return None;
+    }
+    let mut lines = Vec::new();
+    let mut token = parent.first_token()?.prev_token();
+    while let Some(current) = token {
+        match current.kind() {
+            SyntaxKind::Token(TokenKind::WHITESPACE) => {}
+            SyntaxKind::Token(TokenKind::DOC_COMMENT) => {
+                let text = &current.text()["##".len()..];
+                let text = text.strip_prefix(' ').unwrap_or(text);
+                lines.push(text.trim_end_matches(['\n', '\r']).to_string());
+            }
+            _ => break,
+        }
+        token = current.prev_token();
+    }
+    if lines.is_empty() {
+        return None;
+    }
+    lines.reverse();
+    Some(lines.join("\n"))
+}
+
+/// The `BINDER` of the `let` which binds the variable of an `EXPR_VAR`.
+/// Returns `None` if the variable is bound by a lambda or is free.
+pub fn binder_of(var: &Node) -> Option<Node> {
+    if node_kind(var) != Some(NodeKind::EXPR_VAR) {
+        return None;
+    }
+    let var_name = var.text().to_string();
+    let mut inner = var.clone();
+    for node in var.ancestors().skip(1) {
+        match node_kind(&node) {
+            Some(NodeKind::EXPR_ABS) => {
+                let binder = child(&node, NodeKind::BINDER)?;
+                if name(&binder).as_ref() == Some(&var_name) {
+                    return None;
+                }
+            }
+            // NOTE: The variable is only in scope in the body after `in`,
+            // not in the bindee.
+            Some(NodeKind::EXPR_LET) => {
+                let binder = child(&node, NodeKind::BINDER)?;
+                let in_body = node
+                    .children_with_tokens()
+                    .find(|element| element.kind() == SyntaxKind::Token(TokenKind::KW_IN))
+                    .is_some_and(|token| token.text_range().end() <= inner.text_range().start());
+                if in_body && name(&binder).as_ref() == Some(&var_name) {
+                    return Some(binder);
+                }
+            }
+            _ => {}
+        }
+        inner = node;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use felix_common::srcloc::Mapper;
+
+    use super::*;
+    use crate::Parser;
+
+    /// The documentation of the `let` binding the variable at the end of
+    /// the input.
+    fn docs_of_last_var(input: &str) -> Option<String> {
+        let mapper = Mapper::new(input);
+        let result = Parser::new(input, &mapper).run(Parser::program);
+        assert!(result.problems.is_empty(), "{:?}", result.problems);
+        let var = result
+            .syntax
+            .descendants()
+            .filter(|node| node_kind(node) == Some(NodeKind::EXPR_VAR))
+            .last()
+            .unwrap();
+        docs(&binder_of(&var)?)
+    }
+
+    #[test]
+    fn docs_of_let() {
+        let input = "## The identity.\n##\n##  Twice.\nlet id = λx. x in id";
+        assert_eq!(
+            docs_of_last_var(input).as_deref(),
+            Some("The identity.\n\n Twice.")
+        );
+    }
+
+    #[test]
+    fn docs_of_nested_let() {
+        let input = "let x = unit in\n  ## Why.\n  let y = x in\n  y";
+        assert_eq!(docs_of_last_var(input).as_deref(), Some("Why."));
+    }
+
+    #[test]
+    fn docs_need_doc_comments() {
+        assert_eq!(docs_of_last_var("# Not docs.\nlet x = unit in x"), None);
+        assert_eq!(
+            docs_of_last_var("## Docs.\n#| no |#\nlet x = unit in x"),
+            None
+        );
+        assert_eq!(docs_of_last_var("let x = unit in x"), None);
+    }
+
+    #[test]
+    fn docs_follow_scopes() {
+        let input = "## Outer.\nlet x = unit in λx. x";
+        assert_eq!(docs_of_last_var(input), None);
+        let input = "## Outer.\nlet x = unit in ## Inner.\nlet x = x in unit x";
+        assert_eq!(docs_of_last_var(input).as_deref(), Some("Inner."));
+        let input = "## Outer.\nlet x = unit in ## Inner.\nlet y = x in unit";
+        assert_eq!(docs_of_last_var(input).as_deref(), Some("Outer."));
+    }
+
+    #[test]
+    fn docs_of_definition() {
+        let input = "## Nothing.\nlet x = unit";
+        let mapper = Mapper::new(input);
+        let result = Parser::new(input, &mapper).run(Parser::repl_input);
+        let binder = result
+            .syntax
+            .descendants()
+            .find(|node| node_kind(node) == Some(NodeKind::BINDER))
+            .unwrap();
+        assert_eq!(docs(&binder).as_deref(), Some("Nothing."));
+    }
+}
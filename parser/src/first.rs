@@ -16,7 +16,8 @@ impl First for NodeKind {
     fn first(self) -> TokenKindSet {
         match self {
             PROGRAM => NodeKind::EXPR.first(),
-            EXPR_ABS => TokenKind::GR_LAMBDA_LOWER.into(),
+            DEFINITION => TokenKind::KW_LET.into(),
+            EXPR_ABS => TokenKind::GR_LAMBDA_LOWER | TokenKind::KW_FUN,
             EXPR_APP => NodeKind::EXPR_ATOM.first(),
             EXPR_LET => TokenKind::KW_LET.into(),
             EXPR_PAREN => TokenKind::LPAREN.into(),
@@ -30,6 +31,7 @@ impl First for NodeKind {
             TYPE_VAR => TokenKind::ID_TYPE.into(),
             TYPE_UNIT => TokenKind::TY_UNIT.into(),
             TYPE_META => TokenKindSet::empty(),
+            MISSING => TokenKindSet::empty(),
             ERROR => panic!("NodeKind::ERROR.first() must not be called"),
         }
     }
@@ -50,6 +52,8 @@ mod tests {
     use super::*;
     use crate::{parser::Result, syntax::TRIVIA, Parser};
 
+    type Rule = fn(&mut Parser<'static>, TokenKindSet) -> Result<()>;
+
     #[allow(dead_code)]
     fn compute_first<'a>(rule: fn(&mut Parser<'a>, TokenKindSet) -> Result<()>) -> TokenKindSet {
         fn can_start<'a>(
@@ -63,11 +67,9 @@ mod tests {
             let mut parser = parser.with_root(PROGRAM);
             match rule(&mut parser, TokenKindSet::empty()) {
                 Ok(_) => true,
-                Err(problem) => {
-                    let start = problem.start.column;
-                    assert!(start <= 1);
-                    start == 1
-                }
+                // NOTE: Recovery can skip the UNKNOWN token and fail
+                // further on, but only after consuming the first token.
+                Err(problem) => problem.start.column >= 1,
             }
         }
 
@@ -88,10 +90,8 @@ mod tests {
 
     #[test]
     fn node_kind_first_matches() {
-        let cases: Vec<(
-            NodeKind,
-            fn(&mut Parser<'static>, TokenKindSet) -> Result<()>,
-        )> = vec![
+        let cases: Vec<(NodeKind, Rule)> = vec![
+            (DEFINITION, Parser::definition_or_let),
             (EXPR_ABS, Parser::expr_abs),
             (EXPR_APP, Parser::expr_app),
             (EXPR_LET, Parser::expr_let),
@@ -99,6 +99,7 @@ mod tests {
             (EXPR_VAR, Parser::expr_var),
             (EXPR_UNIT, Parser::expr_unit),
             (BINDER, Parser::binder),
+            (BINDER, Parser::binder_atom),
             (TYPE_ARROW, Parser::type_arrow),
             (TYPE_PAREN, Parser::type_paren),
             (TYPE_VAR, Parser::type_var),
@@ -117,10 +118,7 @@ mod tests {
 
     #[test]
     fn node_kind_set_first_matches() {
-        let cases: Vec<(
-            NodeKindSet,
-            fn(&mut Parser<'static>, TokenKindSet) -> Result<()>,
-        )> = vec![
+        let cases: Vec<(NodeKindSet, Rule)> = vec![
             (NodeKind::EXPR, Parser::expr),
             (NodeKind::EXPR_ATOM, Parser::expr_atom),
             (NodeKind::TYPE, Parser::r#type),
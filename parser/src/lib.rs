@@ -1,8 +1,12 @@
 mod ast;
+pub mod docs;
 mod first;
+mod messages;
+mod typos;
 mod parser;
 mod combinators;
 pub mod rules;
+pub mod style;
 pub mod syntax;
 
 pub use parser::{ParseResult, Parser};
@@ -0,0 +1,49 @@
+//! Human-readable descriptions of what the parser expected and found.
+use crate::first::First;
+use crate::style::{self, Style};
+use crate::syntax::{NodeKind, TokenKind, TokenKindSet};
+
+/// Sets of tokens which are described as a whole when all of them are
+/// expected, in order of preference.
+fn categories() -> [(TokenKindSet, &'static str); 3] {
+    [
+        (NodeKind::EXPR.first(), "an expression"),
+        (NodeKind::EXPR_ATOM.first(), "an argument"),
+        (NodeKind::TYPE.first(), "a type"),
+    ]
+}
+
+/// Describe a set of expected tokens, e.g. "an expression or `in`".
+/// Categories come first, then the remaining tokens in declaration order
+/// and the end of the input last.
+pub(crate) fn expected(mut set: TokenKindSet) -> String {
+    let mut items = Vec::new();
+    for (category, description) in categories() {
+        if set.is_superset(category) {
+            items.push(description);
+            set -= category;
+        }
+    }
+    let eof = set.remove(TokenKind::EOF);
+    items.extend(set.iter().map(TokenKind::description));
+    if eof {
+        items.push(TokenKind::EOF.description());
+    }
+    match items.as_slice() {
+        [] => String::from("nothing"),
+        [item] => item.to_string(),
+        [init @ .., last] => format!("{} or {}", init.join(", "), last),
+    }
+}
+
+/// Describe a token found in the input. Tokens with a fixed spelling are
+/// described by their kind, all others by their text.
+pub(crate) fn found(token: TokenKind, text: &str) -> String {
+    match token {
+        TokenKind::EOF => String::from(TokenKind::EOF.description()),
+        TokenKind::ID_EXPR | TokenKind::ID_TYPE => format!("`{}`", text),
+        _ if style::spelling(token, Style::Ascii).is_some() => format!("`{}`", text),
+        TokenKind::UNKNOWN => format!("{} `{}`", token.description(), text),
+        _ => String::from(token.description()),
+    }
+}
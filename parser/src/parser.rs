@@ -1,9 +1,14 @@
-use felix_common::{srcloc::Mapper, Problem, SrcSpan};
+use felix_common::{problem::Suggestion, srcloc::Mapper, Code, Problem, SrcSpan};
 use logos::Logos;
 
+use crate::first::First;
 use crate::syntax::{self, NodeKind, TokenKind, TokenKindSet};
-use NodeKind::ERROR;
-use TokenKind::EOF;
+use crate::{messages, typos};
+use NodeKind::{ERROR, MISSING};
+use TokenKind::{BLOCK_COMMENT, DOT, EOF, EQUALS, KW_IN, LPAREN, OP_ARROW, RPAREN, UNKNOWN};
+
+/// The tokens the parser inserts when they are missing.
+const INSERTABLE: TokenKindSet = enumset::enum_set!(KW_IN | DOT | EQUALS | RPAREN);
 
 #[cfg(test)]
 static FAKE_INPUT: &str = "0123456789ABCDEF";
@@ -15,18 +20,33 @@ static FAKE_MAPPER: std::sync::LazyLock<Mapper> =
 /// Stateful parser for the Rufus language.
 pub struct Parser<'a> {
     input: &'a str,
-    mapper: &'a Mapper<'a>,
+    mapper: &'a Mapper,
     lexer:
         Box<dyn Iterator<Item = (std::result::Result<TokenKind, ()>, std::ops::Range<usize>)> + 'a>,
     peeked: Option<(TokenKind, SrcSpan<u32>)>,
+    /// A token the parser decided to insert in front of the peeked one.
+    missing: Option<TokenKind>,
+    /// The end of the last token consumed, where missing tokens go.
+    last_end: u32,
     trivia: Vec<(TokenKind, SrcSpan<u32>)>,
     preserve_trivia: bool,
     open_node_stack: Vec<NodeKind>,
+    /// The spans of the `(`s whose `)` has not been found yet.
+    open_parens: Vec<SrcSpan<u32>>,
     builder: rowan::GreenNodeBuilder<'a>,
     problems: Vec<Problem>,
+    /// The follow sets of the rules run by `with_recovery` which are still
+    /// running. Recovery skips to a token in one of them.
+    anchors: Vec<TokenKindSet>,
+    /// Whether a problem has been reported and no token has been consumed
+    /// since. Problems reported while recovering are mostly consequences of
+    /// the first one and hence dropped.
+    recovering: bool,
 }
 
-pub(crate) type Result<T> = std::result::Result<T, Problem>;
+// NOTE: Problems are boxed since they are much larger than anything we
+// return in the success case.
+pub(crate) type Result<T> = std::result::Result<T, Box<Problem>>;
 
 pub struct ParseResult {
     pub syntax: syntax::Node,
@@ -43,11 +63,16 @@ impl<'a> Parser<'a> {
             mapper,
             lexer: Box::new(TokenKind::lexer(input).spanned()),
             peeked: None,
+            missing: None,
+            last_end: 0,
             trivia: Vec::new(),
             preserve_trivia: true,
             open_node_stack: Vec::new(),
+            open_parens: Vec::new(),
             builder: rowan::GreenNodeBuilder::new(),
             problems: Vec::new(),
+            anchors: Vec::new(),
+            recovering: false,
         }
     }
 
@@ -68,11 +93,16 @@ impl<'a> Parser<'a> {
                     .map(|(index, token)| (Ok(token), index..index + 1)),
             ),
             peeked: None,
+            missing: None,
+            last_end: 0,
             trivia: Vec::new(),
             preserve_trivia: true,
             open_node_stack: Vec::new(),
+            open_parens: Vec::new(),
             builder: rowan::GreenNodeBuilder::new(),
             problems: Vec::new(),
+            anchors: Vec::new(),
+            recovering: false,
         }
     }
 
@@ -81,6 +111,9 @@ impl<'a> Parser<'a> {
         assert_eq!(self.peek(), EOF);
         assert!(self.trivia.is_empty());
         let green_node = self.builder.finish();
+        // NOTE: The lexer reports problems when the parser peeks at a token,
+        // which can be before the parser reports one at an earlier token.
+        self.problems.sort_by_key(|problem| problem.span.start);
         ParseResult {
             syntax: rowan::SyntaxNode::new_root(green_node),
             problems: self.problems,
@@ -93,7 +126,7 @@ impl<'a> Parser<'a> {
         rule: fn(&mut Self, TokenKindSet) -> Result<()>,
     ) -> ParseResult {
         if let Err(problem) = rule(&mut self, TokenKind::EOF.into()) {
-            self.push_problem(problem);
+            self.push_problem(*problem);
         } else {
             assert_eq!(self.peek(), EOF);
         }
@@ -105,18 +138,26 @@ impl<'a> Parser<'a> {
     }
 
     pub(crate) fn push_problem(&mut self, problem: Problem) {
-        self.problems.push(problem);
+        if !self.recovering {
+            self.problems.push(problem);
+            self.recovering = true;
+        }
     }
 
-    pub(crate) fn error(&mut self, message: String) -> Problem {
+    pub(crate) fn error(&mut self, code: Code, message: String) -> Problem {
         let span = self.peeked.unwrap().1;
+        self.error_at(span, code, message)
+    }
+
+    fn error_at(&mut self, span: SrcSpan<u32>, code: Code, message: String) -> Problem {
         let node = self
             .open_node_stack
             .last()
             .copied()
             .unwrap_or(NodeKind::ERROR);
         let source = format!("parser/{}", node.to_string().to_ascii_lowercase());
-        self.mapper.error(span.start, span.end, source, message)
+        self.mapper
+            .error(span.start, span.end, code, source, message)
     }
 
     pub(crate) fn commit_trivia(&mut self) {
@@ -131,6 +172,13 @@ impl<'a> Parser<'a> {
     }
 
     fn peek_with_span(&mut self) -> (TokenKind, SrcSpan<u32>) {
+        if let Some(token) = self.missing {
+            let span = SrcSpan {
+                start: self.last_end,
+                end: self.last_end,
+            };
+            return (token, span);
+        }
         if let Some(token_span) = self.peeked {
             return token_span;
         }
@@ -145,8 +193,7 @@ impl<'a> Parser<'a> {
                     break (TokenKind::EOF, span);
                 }
                 Some((token, range)) => {
-                    let token = token.unwrap_or(TokenKind::UNKNOWN);
-                    let span = SrcSpan::from_range(range);
+                    let (token, span) = self.lex(token, range);
                     if token.is_trivia() {
                         self.trivia.push((token, span));
                     } else {
@@ -159,31 +206,242 @@ impl<'a> Parser<'a> {
         token_span
     }
 
+    /// Turn a lexeme into a token. Typos are reported and, if we know which
+    /// token was meant, replaced by it.
+    fn lex(
+        &mut self,
+        token: std::result::Result<TokenKind, ()>,
+        range: std::ops::Range<usize>,
+    ) -> (TokenKind, SrcSpan<u32>) {
+        let input = self.input;
+        let mut span = SrcSpan::from_range(range);
+        let text = &input[span.into_range()];
+        if matches!(token, Ok(EQUALS) | Err(()))
+            && typos::is_arrow_start(text)
+            && input[span.end as usize..].starts_with('>')
+        {
+            // NOTE: The lexer never makes `>` part of a longer lexeme.
+            if let Some((_, range)) = self.lexer.next() {
+                span.end = range.end as u32;
+            }
+            self.typo(span, "->", None);
+            return (OP_ARROW, span);
+        }
+        match token {
+            Ok(token) => (token, span),
+            Err(()) if text.starts_with("#|") => {
+                self.unclosed_block_comment(span);
+                (BLOCK_COMMENT, span)
+            }
+            Err(()) => match typos::lookalike(text) {
+                Some(lookalike) => {
+                    self.typo(span, lookalike.replacement, lookalike.name);
+                    // NOTE: The parser would report a letter we cannot read
+                    // as a token a second time.
+                    if lookalike.token == UNKNOWN {
+                        self.recovering = true;
+                    }
+                    (lookalike.token, span)
+                }
+                None => (UNKNOWN, span),
+            },
+        }
+    }
+
+    /// Report a block comment which extends to the end of the input.
+    fn unclosed_block_comment(&mut self, span: SrcSpan<u32>) {
+        let end = SrcSpan {
+            start: span.end,
+            end: span.end,
+        };
+        let opener = SrcSpan {
+            start: span.start,
+            end: span.start + 2,
+        };
+        let problem = self
+            .mapper
+            .error(
+                end.start,
+                end.end,
+                Code::UNEXPECTED_TOKEN,
+                String::from("parser/lexer"),
+                format!(
+                    "Expected `|#`, found {}.",
+                    TokenKind::EOF.description()
+                ),
+            )
+            .with_label(
+                self.mapper
+                    .label(opener, String::from("unclosed block comment")),
+            )
+            .with_suggestion(Suggestion {
+                message: String::from("Insert `|#`"),
+                edits: vec![self.mapper.text_edit(end, String::from("|#"))],
+            });
+        self.problems.push(problem);
+    }
+
+    fn typo(&mut self, span: SrcSpan<u32>, replacement: &str, name: Option<&str>) {
+        let text = &self.input[span.into_range()];
+        let mut problem = self
+            .mapper
+            .error(
+                span.start,
+                span.end,
+                Code::UNEXPECTED_CHARACTER,
+                String::from("parser/lexer"),
+                format!("Found `{}`, did you mean `{}`?", text, replacement),
+            )
+            .with_suggestion(Suggestion {
+                message: format!("Replace `{}` with `{}`", text, replacement),
+                edits: vec![self.mapper.text_edit(span, String::from(replacement))],
+            });
+        if let Some(name) = name {
+            problem = problem.with_note(format!("`{}` is {}.", text, name));
+        }
+        // NOTE: Problems found by the lexer do not depend on the state of
+        // the parser and are always reported.
+        self.problems.push(problem);
+    }
+
     pub(crate) fn peek(&mut self) -> TokenKind {
         self.peek_with_span().0
     }
 
-    pub(crate) fn expecation_error(&mut self, token: TokenKind, expected: TokenKindSet) -> Problem {
-        self.error(format!("Found {}, expected {}.", token, expected))
+    pub(crate) fn peek_span(&mut self) -> SrcSpan<u32> {
+        self.peek_with_span().1
+    }
+
+    pub(crate) fn expecation_error(
+        &mut self,
+        token: TokenKind,
+        expected: TokenKindSet,
+    ) -> Box<Problem> {
+        let span = self.peek_with_span().1;
+        let found = messages::found(token, &self.input[span.into_range()]);
+        let mut problem = self.error(
+            Code::UNEXPECTED_TOKEN,
+            format!(
+                "Expected {}, found {}.",
+                messages::expected(expected),
+                found
+            ),
+        );
+        problem.expected = expected.iter().map(|token| token.to_string()).collect();
+        if expected.contains(RPAREN) {
+            problem = self.with_opener(problem);
+        } else if token == RPAREN && self.open_parens.is_empty() {
+            problem = problem.with_suggestion(Suggestion {
+                message: String::from("Remove the unmatched `)`"),
+                edits: vec![self.mapper.text_edit(span, String::new())],
+            });
+        }
+        Box::new(problem)
+    }
+
+    /// Point at the `(` of the innermost parentheses which are still open.
+    fn with_opener(&self, problem: Problem) -> Problem {
+        match self.open_parens.last() {
+            Some(&opener) => problem.with_label(
+                self.mapper
+                    .label(opener, String::from("unclosed parenthesis")),
+            ),
+            None => problem,
+        }
+    }
+
+    /// Add a suggestion to replace a misspelled keyword to a problem found
+    /// after it. The keyword has been parsed as a name at `span`.
+    pub(crate) fn misspelled_keyword(
+        &mut self,
+        problem: Box<Problem>,
+        span: SrcSpan<u32>,
+        keyword: TokenKind,
+    ) -> Box<Problem> {
+        let text = &self.input[span.into_range()];
+        let spelling = keyword.description().trim_matches('`');
+        if !typos::is_near(text, spelling) {
+            return problem;
+        }
+        Box::new(problem.with_suggestion(Suggestion {
+            message: format!("Replace `{}` with `{}`", text, spelling),
+            edits: vec![self.mapper.text_edit(span, String::from(spelling))],
+        }))
     }
 
     pub(crate) fn expect(&mut self, expected: TokenKindSet) -> Result<TokenKind> {
         let token = self.peek();
         if token.is(expected) {
             Ok(token)
+        } else if let Some(missing) = self.insertion(token, expected) {
+            let span = SrcSpan {
+                start: self.last_end,
+                end: self.last_end,
+            };
+            let found = messages::found(token, &self.input[self.peeked.unwrap().1.into_range()]);
+            let mut problem = self.error_at(
+                span,
+                Code::UNEXPECTED_TOKEN,
+                format!("Expected {}, found {}.", missing.description(), found),
+            );
+            problem.expected = vec![missing.to_string()];
+            let text = match missing {
+                KW_IN => " in",
+                EQUALS => " =",
+                _ => missing.description().trim_matches('`'),
+            };
+            problem = problem.with_suggestion(Suggestion {
+                message: format!("Insert {}", missing.description()),
+                edits: vec![self.mapper.text_edit(span, String::from(text))],
+            });
+            if missing == RPAREN {
+                problem = self.with_opener(problem);
+            }
+            self.push_problem(problem);
+            self.missing = Some(missing);
+            Ok(missing)
         } else {
             Err(self.expecation_error(token, expected))
         }
     }
 
+    /// The token to insert in front of an unexpected token, if any. We
+    /// only insert tokens which end a part of a construct and only if
+    /// exactly one of them is expected. The unexpected token must be able
+    /// to follow the inserted one, or be the end of the input.
+    fn insertion(&self, token: TokenKind, expected: TokenKindSet) -> Option<TokenKind> {
+        let mut insertable = (expected & INSERTABLE).iter();
+        let missing = insertable.next()?;
+        if insertable.next().is_some() {
+            return None;
+        }
+        let follow = match missing {
+            KW_IN | DOT | EQUALS => NodeKind::EXPR.first(),
+            _ => TokenKindSet::empty(),
+        };
+        (token == EOF || token.is(follow)).then_some(missing)
+    }
+
     pub(crate) fn advance(&mut self, expected: impl Into<TokenKindSet>) -> TokenKind {
+        let expected = expected.into();
+        if let Some(token) = self.missing.take() {
+            // NOTE: The missing token goes before any trivia and does not
+            // end recovery since nothing from the input has been consumed.
+            assert!(token.is(expected));
+            self.builder.start_node(MISSING.into());
+            self.builder.token(token.into(), "");
+            self.builder.finish_node();
+            return token;
+        }
         let (token, span) = self.peek_with_span();
-        assert!(token.is(expected.into()));
+        assert!(token.is(expected));
         assert!(token != EOF);
         self.commit_trivia();
         self.builder
             .token(token.into(), &self.input[span.into_range()]);
         self.peeked = None;
+        self.last_end = span.end;
+        self.recovering = false;
         token
     }
 
@@ -204,12 +462,53 @@ impl<'a> Parser<'a> {
         if self.peek().is(expected) {
             return self.peek();
         }
+        let recovering = self.recovering;
         let mut parser = self.with_node(ERROR);
         while !parser.peek().is(expected) {
             let token = parser.peek();
             parser.advance(token);
         }
-        parser.peek()
+        let token = parser.peek();
+        drop(parser);
+        self.recovering = recovering;
+        token
+    }
+
+    /// Run a rule and recover if it fails: report its problem and skip
+    /// tokens until one in its follow set or in the follow set of a rule
+    /// around it is found. Parsing then continues after the rule. If it
+    /// stopped at a token from an outer follow set, the problem reported
+    /// for it will be dropped.
+    pub(crate) fn with_recovery(
+        &mut self,
+        rule: fn(&mut Self, TokenKindSet) -> Result<()>,
+        follow: TokenKindSet,
+    ) {
+        self.anchors.push(follow);
+        let result = rule(self, follow);
+        self.anchors.pop();
+        if let Err(problem) = result {
+            self.push_problem(*problem);
+            let anchors = self
+                .anchors
+                .iter()
+                .fold(follow, |acc, anchor| acc | *anchor);
+            self.skip_until(anchors);
+        }
+    }
+
+    /// Parse a rule in parentheses. Problems about the `)` point at the `(`.
+    pub(crate) fn parenthesized(
+        &mut self,
+        rule: fn(&mut Self, TokenKindSet) -> Result<()>,
+    ) -> Result<()> {
+        let opener = self.peek_span();
+        self.expect_advance(LPAREN)?;
+        self.open_parens.push(opener);
+        self.with_recovery(rule, RPAREN.into());
+        let result = self.expect_advance(RPAREN);
+        self.open_parens.pop();
+        result.map(|_| ())
     }
 
     /// Return a checkpoint for usage with `open_node_at` and `with_node_at`.
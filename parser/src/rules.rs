@@ -9,35 +9,83 @@ use TokenKind::*;
 impl<'a> Parser<'a> {
     pub fn program(&mut self) {
         let mut parser = self.with_root(PROGRAM);
-        if let Err(problem) = parser.expr(EOF.into()) {
-            parser.push_problem(problem);
-        }
+        parser.with_recovery(Self::expr, EOF.into());
+        parser.skip_until(EOF);
+    }
+
+    /// Parse an input to the REPL, which is either a program or a
+    /// definition `let BINDER = EXPR` without a body. Both are wrapped in a
+    /// `PROGRAM` node.
+    pub fn repl_input(&mut self) {
+        let mut parser = self.with_root(PROGRAM);
+        parser.with_recovery(Self::definition_or_expr, EOF.into());
         parser.skip_until(EOF);
     }
 
+    fn definition_or_expr(&mut self, follow: TokenKindSet) -> Result<()> {
+        match self.peek() {
+            KW_LET => self.definition_or_let(follow),
+            _ => self.expr(follow),
+        }
+    }
+
+    /// Parse a `DEFINITION` or an `EXPR_LET`. They only differ in the
+    /// presence of `in`, which is why the node is opened retroactively.
+    pub(crate) fn definition_or_let(&mut self, follow: TokenKindSet) -> Result<()> {
+        let checkpoint = self.checkpoint();
+        self.expect_advance(KW_LET)?;
+        self.with_recovery(Self::definition_head, KW_IN | follow);
+        match self.expect(KW_IN | follow) {
+            Ok(KW_IN) => {
+                let mut parser = self.with_node_at(checkpoint, EXPR_LET);
+                parser.advance(KW_IN);
+                parser.expr(follow)
+            }
+            result => {
+                self.with_node_at(checkpoint, DEFINITION);
+                result.map(|_| ())
+            }
+        }
+    }
+
+    /// Parse `BINDER = EXPR` after the `let` of a definition.
+    fn definition_head(&mut self, follow: TokenKindSet) -> Result<()> {
+        self.with_recovery(Self::binder, EQUALS.into());
+        self.expect_advance(EQUALS)?;
+        self.expr(follow)
+    }
+
     pub(crate) fn expr(&mut self, follow: TokenKindSet) -> Result<()> {
         match self.peek() {
-            GR_LAMBDA_LOWER => self.expr_abs(follow),
+            GR_LAMBDA_LOWER | KW_FUN => self.expr_abs(follow),
             KW_LET => self.expr_let(follow),
             token if token.starts(EXPR_APP) => self.expr_app(follow),
             token => Err(self.expecation_error(token, NodeKind::EXPR.first())),
         }
     }
 
+    /// Parse `λ BINDER . EXPR` or its alternative form `fun BINDER -> EXPR`.
     pub(crate) fn expr_abs(&mut self, follow: TokenKindSet) -> Result<()> {
         let mut parser = self.with_node(EXPR_ABS);
-        parser.expect_advance(GR_LAMBDA_LOWER)?;
-        parser.binder(DOT.into())?;
-        parser.expect_advance(DOT)?;
+        match parser.expect_advance(GR_LAMBDA_LOWER | KW_FUN)? {
+            KW_FUN => {
+                parser.with_recovery(Self::binder_atom, OP_ARROW.into());
+                parser.expect_advance(OP_ARROW)?;
+            }
+            _ => {
+                parser.with_recovery(Self::binder, DOT.into());
+                parser.expect_advance(DOT)?;
+            }
+        }
         parser.expr(follow)
     }
 
     pub(crate) fn expr_let(&mut self, follow: TokenKindSet) -> Result<()> {
         let mut parser = self.with_node(EXPR_LET);
         parser.expect_advance(KW_LET)?;
-        parser.binder(EQUALS.into())?;
+        parser.with_recovery(Self::binder, EQUALS.into());
         parser.expect_advance(EQUALS)?;
-        parser.expr(KW_IN.into())?;
+        parser.with_recovery(Self::expr, KW_IN.into());
         parser.expect_advance(KW_IN)?;
         parser.expr(follow)
     }
@@ -45,8 +93,18 @@ impl<'a> Parser<'a> {
     pub(crate) fn expr_app(&mut self, follow: TokenKindSet) -> Result<()> {
         let atom_first = NodeKind::EXPR_ATOM.first();
         let checkpoint = self.checkpoint();
+        let head = self.peek_span();
         self.expr_atom(atom_first | follow)?;
-        while self.expect(atom_first | follow)?.is(atom_first) {
+        // NOTE: A misspelled `let` is parsed as the head of an application
+        // until its `=` is found.
+        while self
+            .expect(atom_first | follow)
+            .map_err(|problem| match self.peek() {
+                EQUALS => self.misspelled_keyword(problem, head, KW_LET),
+                _ => problem,
+            })?
+            .is(atom_first)
+        {
             self.with_node_at(checkpoint, EXPR_APP)
                 .expr_atom(atom_first | follow)?;
         }
@@ -63,11 +121,7 @@ impl<'a> Parser<'a> {
     }
 
     pub(crate) fn expr_paren(&mut self, _follow: TokenKindSet) -> Result<()> {
-        let mut parser = self.with_node(EXPR_PAREN);
-        parser.expect_advance(LPAREN)?;
-        parser.expr(RPAREN.into())?;
-        parser.expect_advance(RPAREN)?;
-        Ok(())
+        self.with_node(EXPR_PAREN).parenthesized(Self::expr)
     }
 
     pub(crate) fn expr_var(&mut self, _follow: TokenKindSet) -> Result<()> {
@@ -105,11 +159,7 @@ impl<'a> Parser<'a> {
     }
 
     pub(crate) fn type_paren(&mut self, _follow: TokenKindSet) -> Result<()> {
-        let mut parser = self.with_node(TYPE_PAREN);
-        parser.expect_advance(LPAREN)?;
-        parser.r#type(RPAREN.into())?;
-        parser.expect_advance(RPAREN)?;
-        Ok(())
+        self.with_node(TYPE_PAREN).parenthesized(Self::r#type)
     }
 
     pub(crate) fn type_var(&mut self, _follow: TokenKindSet) -> Result<()> {
@@ -123,11 +173,25 @@ impl<'a> Parser<'a> {
     }
 
     pub(crate) fn binder(&mut self, follow: TokenKindSet) -> Result<()> {
+        self.binder_with(Self::r#type, follow)
+    }
+
+    /// Parse a `BINDER` whose type is a `TYPE_ATOM`. This is used after
+    /// `fun`, where an arrow would end the binder.
+    pub(crate) fn binder_atom(&mut self, follow: TokenKindSet) -> Result<()> {
+        self.binder_with(Self::type_atom, follow)
+    }
+
+    fn binder_with(
+        &mut self,
+        r#type: fn(&mut Self, TokenKindSet) -> Result<()>,
+        follow: TokenKindSet,
+    ) -> Result<()> {
         let mut parser = self.with_node(BINDER);
         parser.with_node(NAME).expect_advance(ID_EXPR)?;
         if parser.expect(COLON | follow)? == COLON {
             parser.advance(COLON);
-            parser.r#type(follow)?;
+            r#type(&mut parser, follow)?;
         }
         Ok(())
     }
@@ -0,0 +1,127 @@
+//! The ASCII and Unicode spellings of tokens and the normaliser which
+//! rewrites a program to use only one of them.
+use logos::Logos;
+
+use crate::syntax::TokenKind::{self, *};
+
+/// A choice between the spellings of tokens which have both an ASCII and
+/// a Unicode one, like `->` and `→`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Style {
+    Ascii,
+    Unicode,
+}
+
+/// The spelling of the token in the given style, if it has one in both
+/// styles.
+pub fn spelling(token: TokenKind, style: Style) -> Option<&'static str> {
+    let (ascii, unicode) = match token {
+        KW_FORALL => ("forall", "∀"),
+        GR_LAMBDA_LOWER => ("\\", "λ"),
+        OP_ARROW => ("->", "→"),
+        OP_INTER => ("/\\", "∧"),
+        OP_UNION => ("\\/", "∨"),
+        OP_COMPL => ("~", "¬"),
+        _ => return None,
+    };
+    match style {
+        Style::Ascii => Some(ascii),
+        Style::Unicode => Some(unicode),
+    }
+}
+
+/// Rewrite the input such that all tokens with two spellings use the one
+/// of the given style. Everything else, including comments and text the
+/// lexer cannot read, is kept as is. The input does not need to parse.
+///
+/// NOTE: Lambdas of the form `fun x -> e` keep their form. Only their
+/// arrow is rewritten.
+pub fn normalize(input: &str, style: Style) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut rewritten = false;
+    for (token, range) in TokenKind::lexer(input).spanned() {
+        let spelling = token.ok().and_then(|token| spelling(token, style));
+        let text = spelling.unwrap_or(&input[range]);
+        // NOTE: A new spelling must not merge with its neighbours into a
+        // different token, like `∀a` into `foralla`.
+        if (rewritten || spelling.is_some()) && joins(&output, text) {
+            output.push(' ');
+        }
+        output.push_str(text);
+        rewritten = spelling.is_some();
+    }
+    output
+}
+
+/// Whether the end of `before` and the start of `after` would lex as part
+/// of the same token, like `forall` and `a`, or `\` and `/\`.
+fn joins(before: &str, after: &str) -> bool {
+    let (Some(last), Some(first)) = (before.chars().next_back(), after.chars().next()) else {
+        return false;
+    };
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    (is_word(last) && is_word(first)) || (last == '\\' && first == '/')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spellings_lex_as_their_token() {
+        for token in <TokenKind as strum::VariantArray>::VARIANTS {
+            for style in [Style::Ascii, Style::Unicode] {
+                if let Some(spelling) = spelling(*token, style) {
+                    let tokens: Vec<_> = TokenKind::lexer(spelling).collect();
+                    assert_eq!(tokens, [Ok(*token)], "{}", spelling);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn normalize_styles() {
+        let ascii = "# λx → x\nlet id = \\x:Unit -> Unit. x in fun y -> id y";
+        let unicode = "# λx → x\nlet id = λx:Unit → Unit. x in fun y → id y";
+        assert_eq!(normalize(ascii, Style::Unicode), unicode);
+        assert_eq!(normalize(unicode, Style::Ascii), ascii);
+        assert_eq!(normalize(unicode, Style::Unicode), unicode);
+        assert_eq!(normalize("forall /\\ \\/ ~", Style::Unicode), "∀ ∧ ∨ ¬");
+        assert_eq!(normalize("∀ ∧ ∨ ¬", Style::Ascii), "forall /\\ \\/ ~");
+    }
+
+    #[test]
+    fn normalize_keeps_tokens() {
+        let kinds = |input: &str| -> Vec<_> {
+            TokenKind::lexer(input)
+                .filter(|token| *token != Ok(WHITESPACE))
+                .collect()
+        };
+        for input in [
+            "∀a. a→a",
+            "x∀y",
+            "∀∀",
+            "λ∧",
+            "\\∧",
+            "λ∨λ",
+            "∧∨∧",
+            "¬¬a",
+            "λx:Unit→Unit.x",
+            "forall_",
+            "\\/\\",
+            "a/\\b",
+        ] {
+            for style in [Style::Ascii, Style::Unicode] {
+                let output = normalize(input, style);
+                assert_eq!(kinds(&output), kinds(input), "{} became {}", input, output);
+            }
+        }
+        assert_eq!(normalize("∀a", Style::Ascii), "forall a");
+        assert_eq!(normalize("λ∧", Style::Ascii), "\\ /\\");
+    }
+
+    #[test]
+    fn normalize_keeps_unreadable_text() {
+        assert_eq!(normalize("λx. $ → x =>", Style::Ascii), "\\x. $ -> x =>");
+    }
+}
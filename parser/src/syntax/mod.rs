@@ -2,6 +2,7 @@ mod lang;
 mod node;
 mod token;
 
+pub use lang::{FelixLang, SyntaxKind};
 pub use node::*;
 pub use token::*;
 
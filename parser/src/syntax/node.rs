@@ -7,6 +7,7 @@ use strum::VariantArray;
 #[enumset(repr = "u64")]
 pub enum NodeKind {
     PROGRAM,
+    DEFINITION,
 
     EXPR_ABS,
     EXPR_APP,
@@ -25,6 +26,7 @@ pub enum NodeKind {
     TYPE_UNIT,
     TYPE_META, // Placeholder for types in generated syntax.
 
+    MISSING, // Wraps a zero-width token the parser inserted.
     ERROR,
 }
 
@@ -43,6 +45,30 @@ impl NodeKind {
     pub const TYPE: NodeKindSet = enum_set!(Self::TYPE_ARROW | Self::TYPE_ATOM | Self::TYPE_META);
     pub const TYPE_ATOM: NodeKindSet =
         enum_set!(Self::TYPE_PAREN | Self::TYPE_VAR | Self::TYPE_UNIT);
+
+    /// A description of the node for messages to humans, e.g. "a type".
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::PROGRAM => "a program",
+            Self::DEFINITION => "a definition",
+            Self::EXPR_ABS => "a lambda",
+            Self::EXPR_APP => "an application",
+            Self::EXPR_LET => "a let expression",
+            Self::EXPR_PAREN => "a parenthesized expression",
+            Self::EXPR_VAR => "a variable",
+            Self::EXPR_UNIT => "`unit`",
+            Self::EXPR_META => "an expression",
+            Self::BINDER => "a binder",
+            Self::NAME => "a name",
+            Self::TYPE_ARROW => "a function type",
+            Self::TYPE_PAREN => "a parenthesized type",
+            Self::TYPE_VAR => "a type variable",
+            Self::TYPE_UNIT => "`Unit`",
+            Self::TYPE_META => "a type",
+            Self::MISSING => "a missing token",
+            Self::ERROR => "invalid syntax",
+        }
+    }
 }
 
 impl From<NodeKind> for u16 {
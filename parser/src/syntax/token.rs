@@ -8,6 +8,11 @@ use strum::VariantArray;
 #[enumset(repr = "u64")]
 pub enum TokenKind {
     // Keywords
+    #[token("forall")]
+    #[token("∀")]
+    KW_FORALL,
+    #[token("fun")]
+    KW_FUN,
     #[token("in")]
     KW_IN,
     #[token("let")]
@@ -17,6 +22,7 @@ pub enum TokenKind {
 
     // Greek letters
     #[token("λ")]
+    #[token("\\")]
     GR_LAMBDA_LOWER,
 
     // Builtin types
@@ -31,7 +37,17 @@ pub enum TokenKind {
 
     // Operators
     #[token("->")]
+    #[token("→")]
     OP_ARROW,
+    #[token("/\\")]
+    #[token("∧")]
+    OP_INTER,
+    #[token("\\/")]
+    #[token("∨")]
+    OP_UNION,
+    #[token("~")]
+    #[token("¬")]
+    OP_COMPL,
 
     // Separators
     #[token(":")]
@@ -48,8 +64,12 @@ pub enum TokenKind {
     ID_TYPE,
     #[regex(r"\s+")]
     WHITESPACE,
-    #[regex(r"#[^\n]*\n?")]
+    #[regex(r"#([^#|\n][^\n]*)?\n?")]
     COMMENT,
+    #[token("#|", block_comment)]
+    BLOCK_COMMENT,
+    #[regex(r"##[^\n]*\n?")]
+    DOC_COMMENT,
 
     // Special
     UNKNOWN, // Unknown token, used for error recovery.
@@ -70,7 +90,37 @@ pub type Token = rowan::SyntaxToken<super::lang::FelixLang>;
 // );
 // pub const EXPR_PREFIX_OPS: TokenKindSet = enumset::enum_set!(NOT);
 // pub const LITERALS: TokenKindSet = enumset::enum_set!(LIT_NAT | KW_FALSE | KW_TRUE);
-pub const TRIVIA: TokenKindSet = enumset::enum_set!(WHITESPACE | COMMENT);
+pub const COMMENTS: TokenKindSet = enumset::enum_set!(COMMENT | BLOCK_COMMENT | DOC_COMMENT);
+pub const TRIVIA: TokenKindSet =
+    enumset::enum_set!(WHITESPACE | COMMENT | BLOCK_COMMENT | DOC_COMMENT);
+
+/// Skip the rest of a block comment after its `#|`, including nested block
+/// comments. An unclosed block comment extends to the end of the input and
+/// is an error.
+fn block_comment(lexer: &mut logos::Lexer<TokenKind>) -> bool {
+    let rest = lexer.remainder().as_bytes();
+    let mut depth = 1;
+    let mut index = 0;
+    while index + 1 < rest.len() {
+        match &rest[index..index + 2] {
+            b"#|" => {
+                depth += 1;
+                index += 2;
+            }
+            b"|#" => {
+                depth -= 1;
+                index += 2;
+                if depth == 0 {
+                    lexer.bump(index);
+                    return true;
+                }
+            }
+            _ => index += 1,
+        }
+    }
+    lexer.bump(rest.len());
+    false
+}
 
 impl TokenKind {
     pub const LAST: Self = Self::VARIANTS[Self::VARIANTS.len() - 1];
@@ -84,6 +134,36 @@ impl TokenKind {
     pub fn is_trivia(self) -> bool {
         self.is(TRIVIA)
     }
+
+    /// A description of the token for messages to humans, e.g. "`in`" or
+    /// "a name".
+    pub fn description(self) -> &'static str {
+        match self {
+            KW_FORALL => "`∀`",
+            KW_FUN => "`fun`",
+            KW_IN => "`in`",
+            KW_LET => "`let`",
+            KW_UNIT => "`unit`",
+            GR_LAMBDA_LOWER => "`λ`",
+            TY_UNIT => "`Unit`",
+            RPAREN => "`)`",
+            LPAREN => "`(`",
+            OP_ARROW => "`->`",
+            OP_INTER => "`∧`",
+            OP_UNION => "`∨`",
+            OP_COMPL => "`¬`",
+            COLON => "`:`",
+            DOT => "`.`",
+            EQUALS => "`=`",
+            ID_EXPR => "a name",
+            ID_TYPE => "a type name",
+            WHITESPACE => "whitespace",
+            COMMENT | BLOCK_COMMENT => "a comment",
+            DOC_COMMENT => "a doc comment",
+            UNKNOWN => "an invalid character",
+            EOF => "the end of the input",
+        }
+    }
 }
 
 impl From<TokenKind> for u16 {
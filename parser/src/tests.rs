@@ -1,6 +1,7 @@
 use assert_matches::assert_matches;
+use insta::assert_snapshot;
 
-use felix_common::{srcloc::Mapper, SrcLoc};
+use felix_common::{explain, srcloc::Mapper, Code, SrcLoc};
 
 use super::{ast::*, *};
 
@@ -133,8 +134,8 @@ fn expr_success() {
         },
     ];
     for case in cases {
-        let mapper = Mapper::new(&case.input);
-        let parser = Parser::new(&case.input, &mapper).without_trivia();
+        let mapper = Mapper::new(case.input);
+        let parser = Parser::new(case.input, &mapper).without_trivia();
         let result = parser.run_partial(Parser::expr);
         assert_matches!(&result.problems[..], [], "test case {}", case.name);
         let parsed = format!("{:#?}", result.syntax);
@@ -158,10 +159,16 @@ fn expr_failure() {
             start: SrcLoc::new(0, 3),
             source: "parser/error",
         },
+        FailureCase {
+            name: "fun_arrow_annot",
+            input: "fun f:A -> B -> e",
+            start: SrcLoc::new(0, 11),
+            source: "parser/expr_abs",
+        },
     ];
     for case in cases {
-        let mapper = Mapper::new(&case.input);
-        let parser = Parser::new(&case.input, &mapper).without_trivia();
+        let mapper = Mapper::new(case.input);
+        let parser = Parser::new(case.input, &mapper).without_trivia();
         let result = parser.run_partial(Parser::expr);
         assert_matches!(&result.problems[..], [_], "test case {}", case.name);
         let problem = &result.problems[0];
@@ -205,8 +212,8 @@ fn type_success() {
         },
     ];
     for case in cases {
-        let mapper = Mapper::new(&case.input);
-        let parser = Parser::new(&case.input, &mapper).without_trivia();
+        let mapper = Mapper::new(case.input);
+        let parser = Parser::new(case.input, &mapper).without_trivia();
         let result = parser.run_partial(Parser::r#type);
         assert_matches!(&result.problems[..], [], "test case {}", case.name);
         let parsed = format!("{:#?}", result.syntax);
@@ -224,8 +231,8 @@ fn type_failure() {
         source: "parser/error", // TODO(MH): Better error sources.
     }];
     for case in cases {
-        let mapper = Mapper::new(&case.input);
-        let parser = Parser::new(&case.input, &mapper).without_trivia();
+        let mapper = Mapper::new(case.input);
+        let parser = Parser::new(case.input, &mapper).without_trivia();
         let result = parser.run_partial(Parser::r#type);
         assert_matches!(&result.problems[..], [_], "test case {}", case.name);
         let problem = &result.problems[0];
@@ -241,3 +248,659 @@ fn type_failure() {
         );
     }
 }
+
+#[test]
+fn repl_input_success() {
+    let cases = vec![
+        SuccessCase {
+            name: "definition",
+            input: "let x = f e",
+            expect: program(definition(
+                binder("x", None),
+                expr_app(expr_var("f"), expr_var("e")),
+            )),
+        },
+        SuccessCase {
+            name: "definition_annot",
+            input: "let x:T = e",
+            expect: program(definition(binder("x", Some(type_var("T"))), expr_var("e"))),
+        },
+        SuccessCase {
+            name: "definition_nested_let",
+            input: "let x = let y = e1 in e2",
+            expect: program(definition(
+                binder("x", None),
+                expr_let(binder("y", None), expr_var("e1"), expr_var("e2")),
+            )),
+        },
+        SuccessCase {
+            name: "let",
+            input: "let x = e1 in e2",
+            expect: program(expr_let(binder("x", None), expr_var("e1"), expr_var("e2"))),
+        },
+        SuccessCase {
+            name: "expr",
+            input: "f e",
+            expect: program(expr_app(expr_var("f"), expr_var("e"))),
+        },
+    ];
+    for case in cases {
+        let mapper = Mapper::new(case.input);
+        let parser = Parser::new(case.input, &mapper).without_trivia();
+        let result = parser.run(Parser::repl_input);
+        assert_matches!(&result.problems[..], [], "test case {}", case.name);
+        let parsed = format!("{:#?}", result.syntax);
+        let expected = format!("{:#?}", case.expect.into_syntax());
+        pretty_assertions::assert_eq!(parsed, expected, "test case {}", case.name);
+    }
+}
+
+#[test]
+fn repl_input_failure() {
+    let cases = vec![
+        FailureCase {
+            name: "definition_no_bindee",
+            input: "let x =",
+            start: SrcLoc::new(0, 7),
+            source: "parser/program",
+        },
+        FailureCase {
+            name: "definition_trailing",
+            input: "let x = e )",
+            start: SrcLoc::new(0, 10),
+            source: "parser/program",
+        },
+    ];
+    for case in cases {
+        let mapper = Mapper::new(case.input);
+        let parser = Parser::new(case.input, &mapper).without_trivia();
+        let result = parser.run(Parser::repl_input);
+        assert_matches!(&result.problems[..], [_], "test case {}", case.name);
+        let problem = &result.problems[0];
+        assert_eq!(
+            problem.start, case.start,
+            "test case {} ({})",
+            case.name, problem.message
+        );
+        assert_eq!(
+            problem.source, case.source,
+            "test case {} ({})",
+            case.name, problem.message
+        );
+    }
+}
+
+/// The examples in the explanations of the codes the parser reports
+/// report the code if and only if they are meant to fail.
+#[test]
+fn explanation_examples() {
+    for code in Code::ALL
+        .into_iter()
+        .filter(|code| code.as_str().starts_with('P'))
+    {
+        for example in explain::examples(code) {
+            assert_eq!(example.language, explain::Language::Felix);
+            let mapper = Mapper::new(example.source);
+            let result = Parser::new(example.source, &mapper).run(Parser::program);
+            let reported = result.problems.iter().any(|problem| problem.code == code);
+            assert_eq!(
+                reported, example.fails,
+                "code {}, example {:?}, problems {:?}",
+                code, example.source, result.problems
+            );
+        }
+    }
+}
+
+#[test]
+fn messages() {
+    let cases = [
+        ("λx. )", "Expected an expression, found `)`."),
+        ("let x =\n  in x", "Expected an expression, found `in`."),
+        (
+            "let x = unit x",
+            "Expected `in`, found the end of the input.",
+        ),
+        ("let x = unit :", "Expected an argument or `in`, found `:`."),
+        ("λ. x", "Expected a name, found `.`."),
+        ("λx:. x", "Expected a type, found `.`."),
+        ("λx:Unit x", "Expected `.`, found `x`."),
+        ("(λx. x", "Expected `)`, found the end of the input."),
+        ("λx:(Unit. x", "Expected `)` or `->`, found `.`."),
+        ("let Unit = unit in unit", "Expected a name, found `Unit`."),
+        ("fun x. x", "Expected `->` or `:`, found `.`."),
+        ("\\x. →", "Expected an expression, found `→`."),
+        ("λx. ∧", "Expected an expression, found `∧`."),
+        (
+            "x $ y",
+            "Expected an argument or the end of the input, found an invalid character `$`.",
+        ),
+    ];
+    for (input, message) in cases {
+        let mapper = Mapper::new(input);
+        let result = Parser::new(input, &mapper).run(Parser::program);
+        assert_matches!(&result.problems[..], [_, ..], "input {:?}", input);
+        assert_eq!(result.problems[0].message, message, "input {:?}", input);
+    }
+}
+
+/// Tools get the expected tokens by their names.
+#[test]
+fn expected_tokens() {
+    let input = "λx:(Unit. x";
+    let mapper = Mapper::new(input);
+    let result = Parser::new(input, &mapper).run(Parser::program);
+    assert_matches!(&result.problems[..], [_]);
+    assert_eq!(result.problems[0].expected, ["RPAREN", "OP_ARROW"]);
+}
+
+/// The ASCII spellings and `fun` produce the same nodes as the Unicode
+/// ones and `λ`.
+#[test]
+fn alternative_spellings() {
+    let input = "\\f:(Unit → Unit). fun x:Unit -> f x";
+    let mapper = Mapper::new(input);
+    let result = Parser::new(input, &mapper)
+        .without_trivia()
+        .run(Parser::program);
+    assert_matches!(&result.problems[..], []);
+    assert_snapshot!(format!("{:#?}", result.syntax), @r#"
+    PROGRAM@0..30
+      EXPR_ABS@0..30
+        GR_LAMBDA_LOWER@0..1 "\\"
+        BINDER@1..16
+          NAME@1..2
+            ID_EXPR@1..2 "f"
+          COLON@2..3 ":"
+          TYPE_PAREN@3..16
+            LPAREN@3..4 "("
+            TYPE_ARROW@4..15
+              TYPE_UNIT@4..8
+                TY_UNIT@4..8 "Unit"
+              OP_ARROW@8..11 "→"
+              TYPE_UNIT@11..15
+                TY_UNIT@11..15 "Unit"
+            RPAREN@15..16 ")"
+        DOT@16..17 "."
+        EXPR_ABS@17..30
+          KW_FUN@17..20 "fun"
+          BINDER@20..26
+            NAME@20..21
+              ID_EXPR@20..21 "x"
+            COLON@21..22 ":"
+            TYPE_UNIT@22..26
+              TY_UNIT@22..26 "Unit"
+          OP_ARROW@26..28 "->"
+          EXPR_APP@28..30
+            EXPR_VAR@28..29
+              ID_EXPR@28..29 "f"
+            EXPR_VAR@29..30
+              ID_EXPR@29..30 "x"
+    "#);
+}
+
+/// Parse a broken program and render its problems followed by its syntax
+/// tree, to see how much of the tree survives recovery.
+fn parse_broken(input: &str) -> String {
+    let mapper = Mapper::new(input);
+    let result = Parser::new(input, &mapper)
+        .without_trivia()
+        .run(Parser::program);
+    let mut output = String::new();
+    for problem in &result.problems {
+        output.push_str(&format!(
+            "{}:{}-{}:{}: {}\n",
+            problem.start.line,
+            problem.start.column,
+            problem.end.line,
+            problem.end.column,
+            problem.message
+        ));
+    }
+    output.push_str(&format!("{:#?}", result.syntax));
+    output
+}
+
+#[test]
+fn recovery_two_lets() {
+    assert_snapshot!(parse_broken("let x = ) in let y = ) in x"), @r#"
+    0:8-0:9: Expected an expression, found `)`.
+    0:21-0:22: Expected an expression, found `)`.
+    PROGRAM@0..17
+      EXPR_LET@0..17
+        KW_LET@0..3 "let"
+        BINDER@3..4
+          NAME@3..4
+            ID_EXPR@3..4 "x"
+        EQUALS@4..5 "="
+        ERROR@5..6
+          RPAREN@5..6 ")"
+        KW_IN@6..8 "in"
+        EXPR_LET@8..17
+          KW_LET@8..11 "let"
+          BINDER@11..12
+            NAME@11..12
+              ID_EXPR@11..12 "y"
+          EQUALS@12..13 "="
+          ERROR@13..14
+            RPAREN@13..14 ")"
+          KW_IN@14..16 "in"
+          EXPR_VAR@16..17
+            ID_EXPR@16..17 "x"
+    "#);
+}
+
+#[test]
+fn recovery_two_lambdas() {
+    assert_snapshot!(parse_broken("(λx. ) (λy. )"), @r#"
+    0:5-0:6: Expected an expression, found `)`.
+    0:12-0:13: Expected an expression, found `)`.
+    PROGRAM@0..12
+      EXPR_APP@0..12
+        EXPR_PAREN@0..6
+          LPAREN@0..1 "("
+          EXPR_ABS@1..5
+            GR_LAMBDA_LOWER@1..3 "λ"
+            BINDER@3..4
+              NAME@3..4
+                ID_EXPR@3..4 "x"
+            DOT@4..5 "."
+          RPAREN@5..6 ")"
+        EXPR_PAREN@6..12
+          LPAREN@6..7 "("
+          EXPR_ABS@7..11
+            GR_LAMBDA_LOWER@7..9 "λ"
+            BINDER@9..10
+              NAME@9..10
+                ID_EXPR@9..10 "y"
+            DOT@10..11 "."
+          RPAREN@11..12 ")"
+    "#);
+}
+
+#[test]
+fn recovery_missing_names() {
+    assert_snapshot!(parse_broken("λ. λ:Unit. x"), @r#"
+    0:1-0:2: Expected a name, found `.`.
+    0:4-0:5: Expected a name, found `:`.
+    PROGRAM@0..12
+      EXPR_ABS@0..12
+        GR_LAMBDA_LOWER@0..2 "λ"
+        BINDER@2..2
+          NAME@2..2
+        DOT@2..3 "."
+        EXPR_ABS@3..12
+          GR_LAMBDA_LOWER@3..5 "λ"
+          BINDER@5..5
+            NAME@5..5
+          ERROR@5..10
+            COLON@5..6 ":"
+            TY_UNIT@6..10 "Unit"
+          DOT@10..11 "."
+          EXPR_VAR@11..12
+            ID_EXPR@11..12 "x"
+    "#);
+}
+
+#[test]
+fn recovery_missing_body() {
+    assert_snapshot!(parse_broken("let x = λy. in let z = ) in z"), @r#"
+    0:12-0:14: Expected an expression, found `in`.
+    0:23-0:24: Expected an expression, found `)`.
+    PROGRAM@0..20
+      EXPR_LET@0..20
+        KW_LET@0..3 "let"
+        BINDER@3..4
+          NAME@3..4
+            ID_EXPR@3..4 "x"
+        EQUALS@4..5 "="
+        EXPR_ABS@5..9
+          GR_LAMBDA_LOWER@5..7 "λ"
+          BINDER@7..8
+            NAME@7..8
+              ID_EXPR@7..8 "y"
+          DOT@8..9 "."
+        KW_IN@9..11 "in"
+        EXPR_LET@11..20
+          KW_LET@11..14 "let"
+          BINDER@14..15
+            NAME@14..15
+              ID_EXPR@14..15 "z"
+          EQUALS@15..16 "="
+          ERROR@16..17
+            RPAREN@16..17 ")"
+          KW_IN@17..19 "in"
+          EXPR_VAR@19..20
+            ID_EXPR@19..20 "z"
+    "#);
+}
+
+#[test]
+fn recovery_unclosed_type() {
+    assert_snapshot!(parse_broken("let f = λx:(Unit. x in f unit"), @r#"
+    0:16-0:17: Expected `)` or `->`, found `.`.
+    PROGRAM@0..23
+      EXPR_LET@0..23
+        KW_LET@0..3 "let"
+        BINDER@3..4
+          NAME@3..4
+            ID_EXPR@3..4 "f"
+        EQUALS@4..5 "="
+        EXPR_ABS@5..16
+          GR_LAMBDA_LOWER@5..7 "λ"
+          BINDER@7..14
+            NAME@7..8
+              ID_EXPR@7..8 "x"
+            COLON@8..9 ":"
+            TYPE_PAREN@9..14
+              LPAREN@9..10 "("
+              TYPE_UNIT@10..14
+                TY_UNIT@10..14 "Unit"
+          DOT@14..15 "."
+          EXPR_VAR@15..16
+            ID_EXPR@15..16 "x"
+        KW_IN@16..18 "in"
+        EXPR_APP@18..23
+          EXPR_VAR@18..19
+            ID_EXPR@18..19 "f"
+          EXPR_UNIT@19..23
+            KW_UNIT@19..23 "unit"
+    "#);
+}
+
+#[test]
+fn recovery_unclosed_paren() {
+    assert_snapshot!(parse_broken("f (x y"), @r#"
+    0:6-0:6: Expected `)`, found the end of the input.
+    PROGRAM@0..4
+      EXPR_APP@0..4
+        EXPR_VAR@0..1
+          ID_EXPR@0..1 "f"
+        EXPR_PAREN@1..4
+          LPAREN@1..2 "("
+          EXPR_APP@2..4
+            EXPR_VAR@2..3
+              ID_EXPR@2..3 "x"
+            EXPR_VAR@3..4
+              ID_EXPR@3..4 "y"
+          MISSING@4..4
+            RPAREN@4..4 ""
+    "#);
+}
+
+#[test]
+fn recovery_stray_token() {
+    assert_snapshot!(parse_broken("let x = unit unit = unit in x"), @r#"
+    0:18-0:19: Expected an argument or `in`, found `=`.
+    PROGRAM@0..21
+      EXPR_LET@0..21
+        KW_LET@0..3 "let"
+        BINDER@3..4
+          NAME@3..4
+            ID_EXPR@3..4 "x"
+        EQUALS@4..5 "="
+        EXPR_APP@5..13
+          EXPR_UNIT@5..9
+            KW_UNIT@5..9 "unit"
+          EXPR_UNIT@9..13
+            KW_UNIT@9..13 "unit"
+        ERROR@13..18
+          EQUALS@13..14 "="
+          KW_UNIT@14..18 "unit"
+        KW_IN@18..20 "in"
+        EXPR_VAR@20..21
+          ID_EXPR@20..21 "x"
+    "#);
+}
+
+#[test]
+fn insertion_dot() {
+    assert_snapshot!(parse_broken("λx x"), @r#"
+    0:2-0:2: Expected `.`, found `x`.
+    PROGRAM@0..4
+      EXPR_ABS@0..4
+        GR_LAMBDA_LOWER@0..2 "λ"
+        BINDER@2..3
+          NAME@2..3
+            ID_EXPR@2..3 "x"
+        MISSING@3..3
+          DOT@3..3 ""
+        EXPR_VAR@3..4
+          ID_EXPR@3..4 "x"
+    "#);
+}
+
+#[test]
+fn insertion_in() {
+    assert_snapshot!(parse_broken("let f = λx. x λy. f y"), @r#"
+    0:13-0:13: Expected `in`, found `λ`.
+    PROGRAM@0..16
+      EXPR_LET@0..16
+        KW_LET@0..3 "let"
+        BINDER@3..4
+          NAME@3..4
+            ID_EXPR@3..4 "f"
+        EQUALS@4..5 "="
+        EXPR_ABS@5..10
+          GR_LAMBDA_LOWER@5..7 "λ"
+          BINDER@7..8
+            NAME@7..8
+              ID_EXPR@7..8 "x"
+          DOT@8..9 "."
+          EXPR_VAR@9..10
+            ID_EXPR@9..10 "x"
+        MISSING@10..10
+          KW_IN@10..10 ""
+        EXPR_ABS@10..16
+          GR_LAMBDA_LOWER@10..12 "λ"
+          BINDER@12..13
+            NAME@12..13
+              ID_EXPR@12..13 "y"
+          DOT@13..14 "."
+          EXPR_APP@14..16
+            EXPR_VAR@14..15
+              ID_EXPR@14..15 "f"
+            EXPR_VAR@15..16
+              ID_EXPR@15..16 "y"
+    "#);
+}
+
+#[test]
+fn insertion_equals() {
+    assert_snapshot!(parse_broken("let x unit in x"), @r#"
+    0:5-0:5: Expected `=`, found `unit`.
+    PROGRAM@0..11
+      EXPR_LET@0..11
+        KW_LET@0..3 "let"
+        BINDER@3..4
+          NAME@3..4
+            ID_EXPR@3..4 "x"
+        MISSING@4..4
+          EQUALS@4..4 ""
+        EXPR_UNIT@4..8
+          KW_UNIT@4..8 "unit"
+        KW_IN@8..10 "in"
+        EXPR_VAR@10..11
+          ID_EXPR@10..11 "x"
+    "#);
+}
+
+#[test]
+fn insertion_rparen() {
+    assert_snapshot!(parse_broken("f (λx:(Unit -> Unit. x"), @r#"
+    0:19-0:20: Expected `)` or `->`, found `.`.
+    0:22-0:22: Expected `)`, found the end of the input.
+    PROGRAM@0..19
+      EXPR_APP@0..19
+        EXPR_VAR@0..1
+          ID_EXPR@0..1 "f"
+        EXPR_PAREN@1..19
+          LPAREN@1..2 "("
+          EXPR_ABS@2..19
+            GR_LAMBDA_LOWER@2..4 "λ"
+            BINDER@4..17
+              NAME@4..5
+                ID_EXPR@4..5 "x"
+              COLON@5..6 ":"
+              TYPE_PAREN@6..17
+                LPAREN@6..7 "("
+                TYPE_ARROW@7..17
+                  TYPE_UNIT@7..11
+                    TY_UNIT@7..11 "Unit"
+                  OP_ARROW@11..13 "->"
+                  TYPE_UNIT@13..17
+                    TY_UNIT@13..17 "Unit"
+            DOT@17..18 "."
+            EXPR_VAR@18..19
+              ID_EXPR@18..19 "x"
+          MISSING@19..19
+            RPAREN@19..19 ""
+    "#);
+}
+
+/// Parse a program and render its problems together with the program after
+/// applying each of their suggestions.
+fn parse_typos(input: &str) -> String {
+    let mapper = Mapper::new(input);
+    let result = Parser::new(input, &mapper).run(Parser::program);
+    let mut output = String::new();
+    for problem in &result.problems {
+        output.push_str(&format!(
+            "{}:{}: {}[{}]: {}\n",
+            problem.start.line,
+            problem.start.column,
+            problem.severity,
+            problem.code,
+            problem.message
+        ));
+        for label in &problem.labels {
+            output.push_str(&format!(
+                "  {}:{}: {}\n",
+                label.start.line, label.start.column, label.message
+            ));
+        }
+        for note in &problem.notes {
+            output.push_str(&format!("  note: {}\n", note));
+        }
+        for suggestion in &problem.suggestions {
+            let mut fixed = String::from(input);
+            for edit in suggestion.edits.iter().rev() {
+                fixed.replace_range(edit.span.into_range(), &edit.text);
+            }
+            output.push_str(&format!("  help: {}: {}\n", suggestion.message, fixed));
+        }
+    }
+    output
+}
+
+#[test]
+fn typo_capital_lambda() {
+    assert_snapshot!(parse_typos("Λx. x"), @r#"
+    0:0: error[P0003]: Found `Λ`, did you mean `λ`?
+      note: `Λ` is U+039B GREEK CAPITAL LETTER LAMDA.
+      help: Replace `Λ` with `λ`: λx. x
+    "#);
+}
+
+#[test]
+fn typo_fat_arrow() {
+    assert_snapshot!(parse_typos("λx:Unit => Unit. x"), @r#"
+    0:8: error[P0003]: Found `=>`, did you mean `->`?
+      help: Replace `=>` with `->`: λx:Unit -> Unit. x
+    "#);
+}
+
+#[test]
+fn typo_minus_sign_arrow() {
+    assert_snapshot!(parse_typos("λx:Unit −> Unit. x"), @r#"
+    0:8: error[P0003]: Found `−>`, did you mean `->`?
+      help: Replace `−>` with `->`: λx:Unit -> Unit. x
+    "#);
+}
+
+#[test]
+fn typo_let() {
+    assert_snapshot!(parse_typos("lett x = unit in x"), @r#"
+    0:7: error[P0001]: Expected an argument or the end of the input, found `=`.
+      help: Replace `lett` with `let`: let x = unit in x
+    "#);
+}
+
+#[test]
+fn typo_unclosed_paren() {
+    assert_snapshot!(parse_typos("f (g (x y) z"), @r#"
+    0:12: error[P0001]: Expected `)`, found the end of the input.
+      0:2: unclosed parenthesis
+      help: Insert `)`: f (g (x y) z)
+    "#);
+}
+
+#[test]
+fn typo_unmatched_paren() {
+    assert_snapshot!(parse_typos("f x) y"), @r#"
+    0:3: error[P0001]: Expected an argument or the end of the input, found `)`.
+      help: Remove the unmatched `)`: f x y
+    "#);
+}
+
+#[test]
+fn typo_fullwidth() {
+    assert_snapshot!(parse_typos("（λx．x）unit"), @r#"
+    0:0: error[P0003]: Found `（`, did you mean `(`?
+      note: `（` is U+FF08 FULLWIDTH LEFT PARENTHESIS.
+      help: Replace `（` with `(`: (λx．x）unit
+    0:3: error[P0003]: Found `．`, did you mean `.`?
+      note: `．` is U+FF0E FULLWIDTH FULL STOP.
+      help: Replace `．` with `.`: （λx.x）unit
+    0:5: error[P0003]: Found `）`, did you mean `)`?
+      note: `）` is U+FF09 FULLWIDTH RIGHT PARENTHESIS.
+      help: Replace `）` with `)`: （λx．x)unit
+    "#);
+}
+
+#[test]
+fn typo_cyrillic() {
+    assert_snapshot!(parse_typos("let fоo = unit in fоo"), @r#"
+    0:5: error[P0003]: Found `о`, did you mean `o`?
+      note: `о` is U+043E CYRILLIC SMALL LETTER O.
+      help: Replace `о` with `o`: let foo = unit in fоo
+    0:19: error[P0003]: Found `о`, did you mean `o`?
+      note: `о` is U+043E CYRILLIC SMALL LETTER O.
+      help: Replace `о` with `o`: let fоo = unit in foo
+    "#);
+}
+
+#[test]
+fn typo_missing_equals() {
+    assert_snapshot!(parse_typos("let x unit in x"), @r#"
+    0:5: error[P0001]: Expected `=`, found `unit`.
+      help: Insert `=`: let x = unit in x
+    "#);
+}
+
+#[test]
+fn comments() {
+    let input = "## Docs.\n#| a #| nested |# block |# x # line\n";
+    let mapper = Mapper::new(input);
+    let result = Parser::new(input, &mapper).run(Parser::program);
+    assert_matches!(&result.problems[..], []);
+    assert_snapshot!(format!("{:#?}", result.syntax), @r###"
+    PROGRAM@0..45
+      DOC_COMMENT@0..9 "## Docs.\n"
+      BLOCK_COMMENT@9..35 "#| a #| nested |# blo ..."
+      WHITESPACE@35..36 " "
+      EXPR_VAR@36..37
+        ID_EXPR@36..37 "x"
+      WHITESPACE@37..38 " "
+      COMMENT@38..45 "# line\n"
+    "###);
+}
+
+#[test]
+fn comment_unclosed_block() {
+    assert_snapshot!(parse_typos("f #| a #| b |# x"), @r#"
+    0:16: error[P0001]: Expected `|#`, found the end of the input.
+      0:2: unclosed block comment
+      help: Insert `|#`: f #| a #| b |# x|#
+    "#);
+}
@@ -0,0 +1,144 @@
+//! Recognition of typos in the input, such as characters which look like
+//! the ones the language uses or misspelled keywords.
+use crate::syntax::TokenKind::{self, *};
+
+/// Characters which are easily mistaken for one the language uses. We read
+/// the ones in the first group as the token they stand for. The letters in
+/// the second group are only reported since they might be part of names.
+const LOOKALIKES: &[(char, &str, Option<TokenKind>, Option<&str>)] = &[
+    (
+        'Λ',
+        "λ",
+        Some(GR_LAMBDA_LOWER),
+        Some("U+039B GREEK CAPITAL LETTER LAMDA"),
+    ),
+    (
+        '𝜆',
+        "λ",
+        Some(GR_LAMBDA_LOWER),
+        Some("U+1D706 MATHEMATICAL ITALIC SMALL LAMDA"),
+    ),
+    (
+        '（',
+        "(",
+        Some(LPAREN),
+        Some("U+FF08 FULLWIDTH LEFT PARENTHESIS"),
+    ),
+    (
+        '）',
+        ")",
+        Some(RPAREN),
+        Some("U+FF09 FULLWIDTH RIGHT PARENTHESIS"),
+    ),
+    ('：', ":", Some(COLON), Some("U+FF1A FULLWIDTH COLON")),
+    ('．', ".", Some(DOT), Some("U+FF0E FULLWIDTH FULL STOP")),
+    (
+        '＝',
+        "=",
+        Some(EQUALS),
+        Some("U+FF1D FULLWIDTH EQUALS SIGN"),
+    ),
+    ('а', "a", None, Some("U+0430 CYRILLIC SMALL LETTER A")),
+    ('с', "c", None, Some("U+0441 CYRILLIC SMALL LETTER ES")),
+    ('е', "e", None, Some("U+0435 CYRILLIC SMALL LETTER IE")),
+    (
+        'і',
+        "i",
+        None,
+        Some("U+0456 CYRILLIC SMALL LETTER BYELORUSSIAN-UKRAINIAN I"),
+    ),
+    ('о', "o", None, Some("U+043E CYRILLIC SMALL LETTER O")),
+    ('ο', "o", None, Some("U+03BF GREEK SMALL LETTER OMICRON")),
+    ('р', "p", None, Some("U+0440 CYRILLIC SMALL LETTER ER")),
+    ('ѕ', "s", None, Some("U+0455 CYRILLIC SMALL LETTER DZE")),
+    ('х', "x", None, Some("U+0445 CYRILLIC SMALL LETTER HA")),
+    ('у', "y", None, Some("U+0443 CYRILLIC SMALL LETTER U")),
+    ('А', "A", None, Some("U+0410 CYRILLIC CAPITAL LETTER A")),
+    ('В', "B", None, Some("U+0412 CYRILLIC CAPITAL LETTER VE")),
+    ('Е', "E", None, Some("U+0415 CYRILLIC CAPITAL LETTER IE")),
+    ('О', "O", None, Some("U+041E CYRILLIC CAPITAL LETTER O")),
+    ('Р', "P", None, Some("U+0420 CYRILLIC CAPITAL LETTER ER")),
+    ('С', "C", None, Some("U+0421 CYRILLIC CAPITAL LETTER ES")),
+    ('Т', "T", None, Some("U+0422 CYRILLIC CAPITAL LETTER TE")),
+    ('Х', "X", None, Some("U+0425 CYRILLIC CAPITAL LETTER HA")),
+];
+
+/// A character which is easily mistaken for one the language uses.
+pub(crate) struct Lookalike {
+    /// The token the character stands for, or `UNKNOWN` if we cannot read
+    /// it as one.
+    pub(crate) token: TokenKind,
+    pub(crate) replacement: &'static str,
+    /// The Unicode name of the character, if it is not ASCII.
+    pub(crate) name: Option<&'static str>,
+}
+
+pub(crate) fn lookalike(text: &str) -> Option<Lookalike> {
+    let mut chars = text.chars();
+    let char = chars.next()?;
+    if chars.next().is_some() {
+        return None;
+    }
+    let &(_, replacement, token, name) = LOOKALIKES.iter().find(|entry| entry.0 == char)?;
+    Some(Lookalike {
+        token: token.unwrap_or(UNKNOWN),
+        replacement,
+        name,
+    })
+}
+
+/// Whether the text forms an arrow when followed by `>`, as in `=>`.
+pub(crate) fn is_arrow_start(text: &str) -> bool {
+    matches!(text, "=" | "−" | "–")
+}
+
+/// Whether two words differ by at most one insertion, deletion,
+/// substitution or transposition of adjacent characters.
+pub(crate) fn is_near(word: &str, target: &str) -> bool {
+    let word: Vec<char> = word.chars().collect();
+    let target: Vec<char> = target.chars().collect();
+    let prefix = word.iter().zip(&target).take_while(|(a, b)| a == b).count();
+    let (word, target) = (&word[prefix..], &target[prefix..]);
+    match (word.len(), target.len()) {
+        (0, 0) => true,
+        (n, m) if n == m => {
+            word[1..] == target[1..]
+                || (n >= 2
+                    && word[0] == target[1]
+                    && word[1] == target[0]
+                    && word[2..] == target[2..])
+        }
+        (n, m) if n == m + 1 => word[1..] == *target,
+        (n, m) if n + 1 == m => *word == target[1..],
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn near_words() {
+        for word in ["let", "lett", "le", "lte", "elt", "lat", "et"] {
+            assert!(is_near(word, "let"), "{}", word);
+        }
+        for word in ["letter", "l", "tel", "late", "x", ""] {
+            assert!(!is_near(word, "let"), "{}", word);
+        }
+    }
+
+    #[test]
+    fn lookalikes_are_single_characters() {
+        assert_eq!(
+            lookalike("Λ").map(|lookalike| lookalike.token),
+            Some(GR_LAMBDA_LOWER)
+        );
+        assert_eq!(
+            lookalike("о").map(|lookalike| lookalike.token),
+            Some(UNKNOWN)
+        );
+        assert!(lookalike("$").is_none());
+        assert!(lookalike("ΛΛ").is_none());
+    }
+}
@@ -1,11 +1,19 @@
-use std::{any::Any, rc::Rc};
+use std::{any::Any, fmt::Display, rc::Rc};
 use trait_gen::trait_gen;
 
+use felix_common::SrcSpan;
+
 use crate::Type;
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Ident(Rc<String>);
 
+impl Ident {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Binder {
     pub name: Ident,
@@ -13,7 +21,32 @@ pub struct Binder {
 }
 
 #[derive(Clone, Debug)]
-pub struct Expr(Rc<dyn Any>);
+pub struct Expr {
+    node: Rc<dyn Any>,
+    span: Option<SrcSpan<u32>>,
+}
+
+impl Expr {
+    fn new(node: impl Any) -> Self {
+        Self {
+            node: Rc::new(node),
+            span: None,
+        }
+    }
+
+    /// The span of the source code the expression has been lowered from.
+    /// Expressions constructed by other means have no span.
+    pub fn span(&self) -> Option<SrcSpan<u32>> {
+        self.span
+    }
+
+    pub fn with_span(self, span: SrcSpan<u32>) -> Self {
+        Self {
+            span: Some(span),
+            ..self
+        }
+    }
+}
 
 trait ExprNode: 'static {
     fn sub_exprs(&self) -> Vec<&Expr>;
@@ -57,10 +90,7 @@ pub trait FromExpr: Sized {
 impl FromExpr for T {
     #[inline]
     fn from_expr(expr: &Expr) -> Option<Rc<Self>> {
-        match expr.clone().0.downcast() {
-            Ok(x) => Some(x),
-            Err(_) => None,
-        }
+        expr.node.clone().downcast().ok()
     }
 }
 
@@ -92,7 +122,7 @@ impl HasAnnot for T {
 impl<const B: bool, T: FromExpr + HasAnnot> FromExpr for Annot<B, T> {
     fn from_expr(expr: &Expr) -> Option<Rc<Self>> {
         let inner = T::from_expr(expr)?;
-        let annot = inner.annot().as_ref().map(Type::clone);
+        let annot = inner.annot().clone();
         if annot.is_some() == B {
             Some(Rc::new(Annot { inner, annot }))
         } else {
@@ -118,32 +148,81 @@ pub fn binder_annot(name: &str, r#type: Type) -> Binder {
 }
 
 pub fn broken() -> Expr {
-    Expr(Rc::new(Broken))
+    Expr::new(Broken)
 }
 
 pub fn var(name: &str) -> Expr {
     let name = ident(name);
-    Expr(Rc::new(Var { name }))
+    Expr::new(Var { name })
 }
 
 pub fn abs(binder: Binder, body: Expr) -> Expr {
-    Expr(Rc::new(Abs { binder, body }))
+    Expr::new(Abs { binder, body })
 }
 
 pub fn app(fun: Expr, arg: Expr) -> Expr {
-    Expr(Rc::new(App { fun, arg }))
+    Expr::new(App { fun, arg })
 }
 
 pub fn let_(binder: Binder, bindee: Expr, body: Expr) -> Expr {
-    Expr(Rc::new(Let {
+    Expr::new(Let {
         binder,
         bindee,
         body,
-    }))
+    })
 }
 
 pub fn unit() -> Expr {
-    Expr(Rc::new(Unit))
+    Expr::new(Unit)
+}
+
+impl Display for Binder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.annot {
+            Some(r#type) => write!(f, "{}:{}", self.name.as_str(), r#type),
+            None => write!(f, "{}", self.name.as_str()),
+        }
+    }
+}
+
+impl Expr {
+    fn fmt_arg(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if Var::from_expr(self).is_some()
+            || Unit::from_expr(self).is_some()
+            || Broken::from_expr(self).is_some()
+        {
+            write!(f, "{}", self)
+        } else {
+            write!(f, "({})", self)
+        }
+    }
+}
+
+/// Render the expression in the surface syntax.
+impl Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(var) = Var::from_expr(self) {
+            write!(f, "{}", var.name.as_str())
+        } else if let Some(abs) = Abs::from_expr(self) {
+            write!(f, "λ{}. {}", abs.binder, abs.body)
+        } else if let Some(app) = App::from_expr(self) {
+            if App::from_expr(&app.fun).is_some() {
+                write!(f, "{}", app.fun)?;
+            } else {
+                app.fun.fmt_arg(f)?;
+            }
+            write!(f, " ")?;
+            app.arg.fmt_arg(f)
+        } else if let Some(let_) = Let::from_expr(self) {
+            write!(f, "let {} = {} in {}", let_.binder, let_.bindee, let_.body)
+        } else if Unit::from_expr(self).is_some() {
+            write!(f, "unit")
+        } else if Broken::from_expr(self).is_some() {
+            write!(f, "<broken>")
+        } else {
+            write!(f, "<unknown>")
+        }
+    }
 }
 
 #[cfg(test)]
@@ -167,4 +246,33 @@ mod tests {
             Some(Annot { annot: Some(_), .. })
         );
     }
+
+    #[test]
+    fn display() {
+        let unit_to_unit = Type::Arrow(Type::Unit.into(), Type::Unit.into());
+        let cases = vec![
+            (var("x"), "x"),
+            (unit(), "unit"),
+            (broken(), "<broken>"),
+            (
+                abs(binder_annot("f", unit_to_unit), var("f")),
+                "λf:(Unit -> Unit). f",
+            ),
+            (app(app(var("f"), var("x")), var("y")), "f x y"),
+            (app(var("f"), app(var("x"), var("y"))), "f (x y)"),
+            (app(abs(binder("x"), var("x")), unit()), "(λx. x) unit"),
+            (app(var("f"), abs(binder("x"), var("x"))), "f (λx. x)"),
+            (
+                let_(binder("x"), let_(binder("y"), unit(), var("y")), var("x")),
+                "let x = let y = unit in y in x",
+            ),
+            (
+                app(let_(binder("f"), var("g"), var("f")), unit()),
+                "(let f = g in f) unit",
+            ),
+        ];
+        for (expr, expected) in cases {
+            assert_eq!(expr.to_string(), expected);
+        }
+    }
 }
@@ -1,4 +1,6 @@
-use std::{borrow::Borrow, rc::Rc};
+use std::{borrow::Borrow, fmt::Display, rc::Rc};
+
+use felix_common::{srcloc::Mapper, Code, Problem, SrcSpan};
 
 use crate::{
     ast::{self, Expr, FromExpr, Ident},
@@ -17,6 +19,12 @@ enum ContextData {
 #[derive(Clone)]
 pub struct Context(Rc<ContextData>);
 
+impl Default for Context {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Context {
     pub fn new() -> Self {
         Self(Rc::new(ContextData::Empty))
@@ -48,17 +56,94 @@ impl Context {
     }
 }
 
+/// A type error. The spans are those of the innermost expression whose
+/// inference failed, if it has one. `Checker::infer` fills them in.
 #[derive(Debug)]
 pub enum TypeError {
     BrokenNode(Rc<ast::Broken>),
-    UnknownEVar(Ident),
+    UnknownEVar(Ident, Option<SrcSpan<u32>>),
     NoInferRule(Expr),
-    ExpectedArrow { found: Type },
-    TypeMismatch { found: Type, expected: Type },
+    ExpectedArrow {
+        found: Type,
+        span: Option<SrcSpan<u32>>,
+    },
+    TypeMismatch {
+        found: Type,
+        expected: Type,
+        span: Option<SrcSpan<u32>>,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, TypeError>;
 
+impl TypeError {
+    pub fn code(&self) -> Code {
+        match self {
+            // NOTE: Broken nodes stem from syntax errors, which the parser
+            // has reported already.
+            Self::BrokenNode(_) => Code::UNEXPECTED_TOKEN,
+            Self::UnknownEVar(..) => Code::UNKNOWN_VARIABLE,
+            Self::NoInferRule(_) => Code::CANNOT_INFER,
+            Self::ExpectedArrow { .. } => Code::EXPECTED_FUNCTION,
+            Self::TypeMismatch { .. } => Code::TYPE_MISMATCH,
+        }
+    }
+
+    /// The span of the expression the error stems from, if known.
+    pub fn span(&self) -> Option<SrcSpan<u32>> {
+        match self {
+            Self::BrokenNode(_) => None,
+            Self::NoInferRule(expr) => expr.span(),
+            Self::UnknownEVar(_, span)
+            | Self::ExpectedArrow { span, .. }
+            | Self::TypeMismatch { span, .. } => *span,
+        }
+    }
+
+    /// Attribute the error to the expression with the given span unless it
+    /// is attributed to an inner expression already.
+    fn within(mut self, outer: Option<SrcSpan<u32>>) -> Self {
+        match &mut self {
+            Self::UnknownEVar(_, span)
+            | Self::ExpectedArrow { span, .. }
+            | Self::TypeMismatch { span, .. } => *span = span.or(outer),
+            Self::BrokenNode(_) | Self::NoInferRule(_) => {}
+        }
+        self
+    }
+
+    /// Report the error as a problem at the expression it stems from.
+    /// Errors without a span are reported for the whole `program`.
+    pub fn to_problem(&self, mapper: &Mapper, program: &Expr) -> Problem {
+        let span = self.span().or(program.span()).unwrap_or_default();
+        mapper.error(
+            span.start,
+            span.end,
+            self.code(),
+            String::from("type-checker"),
+            self.to_string(),
+        )
+    }
+}
+
+impl Display for TypeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BrokenNode(_) => write!(f, "program contains syntax errors"),
+            Self::UnknownEVar(ident, _) => write!(f, "unknown variable: {}", ident.as_str()),
+            Self::NoInferRule(expr) => write!(f, "cannot infer the type of: {}", expr),
+            Self::ExpectedArrow { found, .. } => {
+                write!(f, "expected a function type, found {}", found)
+            }
+            Self::TypeMismatch {
+                found, expected, ..
+            } => {
+                write!(f, "expected type {}, found {}", expected, found)
+            }
+        }
+    }
+}
+
 pub trait Checker {
     fn lookup(&self, ctx: &Context, evar: &Ident) -> Result<Type>;
     fn check(&self, ctx: &Context, expr: &Expr, r#type: Type) -> Result<()>;
@@ -67,9 +152,11 @@ pub trait Checker {
     fn decompose_arrow(&self, r#type: &Type) -> Result<(Type, Type)>;
 }
 
+type DynInferRule = dyn Fn(&dyn Checker, &Context, &Expr) -> Option<Result<Type>> + Send + Sync;
+
 struct InferRule {
     name: &'static str,
-    rule: Box<dyn Fn(&dyn Checker, &Context, &Expr) -> Option<Result<Type>> + Send + Sync>,
+    rule: Box<DynInferRule>,
 }
 
 impl InferRule {
@@ -110,10 +197,10 @@ impl TypeSystem {
 
 impl Checker for TypeSystem {
     fn lookup(&self, ctx: &Context, evar: &Ident) -> Result<Type> {
-        if let Some(r#type) = ctx.lookup(&evar) {
+        if let Some(r#type) = ctx.lookup(evar) {
             Ok(r#type)
         } else {
-            Err(TypeError::UnknownEVar(evar.clone()))
+            Err(TypeError::UnknownEVar(evar.clone(), None))
         }
     }
 
@@ -125,7 +212,7 @@ impl Checker for TypeSystem {
     fn infer(&self, ctx: &Context, expr: &Expr) -> Result<Type> {
         for rule in &self.infer_rules {
             if let Some(res) = (rule.rule)(self, ctx, expr) {
-                return res;
+                return res.map_err(|error| error.within(expr.span()));
             }
         }
         Err(TypeError::NoInferRule(expr.clone()))
@@ -143,6 +230,7 @@ impl Checker for TypeSystem {
             _ => Err(TypeError::TypeMismatch {
                 found: found.clone(),
                 expected: expected.clone(),
+                span: None,
             }),
         }
     }
@@ -152,6 +240,7 @@ impl Checker for TypeSystem {
             Type::Arrow(param, res) => Ok((param.as_ref().clone(), res.as_ref().clone())),
             _ => Err(TypeError::ExpectedArrow {
                 found: r#type.clone(),
+                span: None,
             }),
         }
     }
@@ -1,6 +1,7 @@
 #![allow(dead_code)]
 mod checker;
-mod ast;
+pub mod ast;
+pub mod lower;
 pub mod stlc;
 mod r#type;
 
@@ -0,0 +1,215 @@
+//! Lowering of concrete syntax trees into the AST the type checker works on.
+use felix_common::SrcSpan;
+use felix_parser::syntax::{Node, NodeKind, SyntaxKind, TokenKind};
+
+use crate::{
+    ast::{self, Binder, Expr},
+    r#type::{self, Type},
+};
+
+/// Lower a `PROGRAM` node or an expression node into an expression. Parts of
+/// the tree which are missing or erroneous are lowered into `Broken` nodes.
+/// Every lowered expression carries the span of the node it stems from.
+pub fn lower(node: &Node) -> Expr {
+    match node_kind(node) {
+        Some(NodeKind::PROGRAM) => child_exprs(node)
+            .next()
+            .map_or_else(ast::broken, |expr| lower(&expr)),
+        _ => {
+            let range = node.text_range();
+            let span = SrcSpan {
+                start: range.start().into(),
+                end: range.end().into(),
+            };
+            match node_kind(node) {
+                // NOTE: Parentheses are transparent, the inner expression
+                // keeps its own span.
+                Some(NodeKind::EXPR_PAREN) => {
+                    expr(node).unwrap_or_else(|| ast::broken().with_span(span))
+                }
+                _ => expr(node).unwrap_or_else(ast::broken).with_span(span),
+            }
+        }
+    }
+}
+
+/// Lower a `PROGRAM` node produced by `Parser::repl_input` into the binder
+/// and the bindee of the definition it contains. Returns `None` if the
+/// program is not a definition or its binder is erroneous.
+pub fn lower_definition(node: &Node) -> Option<(Binder, Expr)> {
+    let definition = child_node(node, NodeKind::DEFINITION)?;
+    let binder = binder(&child_node(&definition, NodeKind::BINDER)?)?;
+    let bindee = child_exprs(&definition)
+        .next()
+        .map_or_else(ast::broken, |expr| lower(&expr));
+    Some((binder, bindee))
+}
+
+fn node_kind(node: &Node) -> Option<NodeKind> {
+    match node.kind() {
+        SyntaxKind::Node(kind) => Some(kind),
+        SyntaxKind::Token(_) => None,
+    }
+}
+
+fn child_exprs(node: &Node) -> impl Iterator<Item = Node> {
+    node.children()
+        .filter(|child| node_kind(child).is_some_and(|kind| NodeKind::EXPR.contains(kind)))
+}
+
+fn child_type(node: &Node) -> Option<Node> {
+    node.children()
+        .find(|child| node_kind(child).is_some_and(|kind| NodeKind::TYPE.contains(kind)))
+}
+
+fn child_node(node: &Node, kind: NodeKind) -> Option<Node> {
+    node.children().find(|child| node_kind(child) == Some(kind))
+}
+
+fn token_text(node: &Node, kind: TokenKind) -> Option<String> {
+    node.children_with_tokens()
+        .filter_map(|child| child.into_token())
+        .find(|token| token.kind() == SyntaxKind::Token(kind))
+        .map(|token| String::from(token.text()))
+}
+
+fn expr(node: &Node) -> Option<Expr> {
+    match node_kind(node)? {
+        NodeKind::EXPR_ABS => {
+            let binder = binder(&child_node(node, NodeKind::BINDER)?)?;
+            let body = lower(&child_exprs(node).next()?);
+            Some(ast::abs(binder, body))
+        }
+        NodeKind::EXPR_APP => {
+            let mut children = child_exprs(node);
+            let fun = lower(&children.next()?);
+            let arg = lower(&children.next()?);
+            Some(ast::app(fun, arg))
+        }
+        NodeKind::EXPR_LET => {
+            let binder = binder(&child_node(node, NodeKind::BINDER)?)?;
+            let mut children = child_exprs(node);
+            let bindee = lower(&children.next()?);
+            let body = lower(&children.next()?);
+            Some(ast::let_(binder, bindee, body))
+        }
+        NodeKind::EXPR_PAREN => Some(lower(&child_exprs(node).next()?)),
+        NodeKind::EXPR_VAR => Some(ast::var(&token_text(node, TokenKind::ID_EXPR)?)),
+        NodeKind::EXPR_UNIT => Some(ast::unit()),
+        _ => None,
+    }
+}
+
+fn binder(node: &Node) -> Option<Binder> {
+    let name = token_text(&child_node(node, NodeKind::NAME)?, TokenKind::ID_EXPR)?;
+    // NOTE: A binder with a colon but a broken type must not turn into a
+    // binder without annotation since that changes its meaning.
+    let annot = if token_text(node, TokenKind::COLON).is_some() {
+        Some(r#type(&child_type(node)?)?)
+    } else {
+        None
+    };
+    Some(Binder {
+        name: ast::ident(&name),
+        annot,
+    })
+}
+
+fn r#type(node: &Node) -> Option<Type> {
+    match node_kind(node)? {
+        NodeKind::TYPE_ARROW => {
+            let mut children = node.children();
+            let param = r#type(&children.next()?)?;
+            let result = r#type(&children.next()?)?;
+            Some(r#type::arrow(param, result))
+        }
+        NodeKind::TYPE_PAREN => r#type(&child_type(node)?),
+        NodeKind::TYPE_VAR => Some(r#type::tvar(&token_text(node, TokenKind::ID_TYPE)?)),
+        NodeKind::TYPE_UNIT => Some(Type::Unit),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use felix_common::srcloc::Mapper;
+    use felix_parser::Parser;
+
+    use super::{lower, lower_definition};
+    use crate::ast::{App, Expr, FromExpr, Let};
+
+    fn lower_str(input: &str) -> String {
+        let mapper = Mapper::new(input);
+        let result = Parser::new(input, &mapper).run(Parser::program);
+        lower(&result.syntax).to_string()
+    }
+
+    #[test]
+    fn lower_success() {
+        let cases = vec![
+            ("λx:T.e", "λx:T. e"),
+            ("λx.e", "λx. e"),
+            ("e1 e2 e3", "e1 e2 e3"),
+            ("e1 (e2 e3)", "e1 (e2 e3)"),
+            ("let x = e1 in e2", "let x = e1 in e2"),
+            ("let x:Unit = unit in x", "let x:Unit = unit in x"),
+            ("λf:(A -> B) -> C. f", "λf:((A -> B) -> C). f"),
+            ("((x))", "x"),
+            ("  λx . x  ", "λx. x"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(lower_str(input), expected, "input: {}", input);
+        }
+    }
+
+    #[test]
+    fn lower_spans() {
+        let input = "let f = (λx. x) in f unit";
+        let mapper = Mapper::new(input);
+        let result = Parser::new(input, &mapper).run(Parser::program);
+        let expr = lower(&result.syntax);
+        let slice = |expr: &Expr| &input[expr.span().unwrap().into_range()];
+        assert_eq!(slice(&expr), input);
+        let let_ = Let::from_expr(&expr).unwrap();
+        assert_eq!(slice(&let_.bindee), "λx. x");
+        assert_eq!(slice(&let_.body), "f unit");
+        let app = App::from_expr(&let_.body).unwrap();
+        assert_eq!(slice(&app.fun), "f");
+        assert_eq!(slice(&app.arg), "unit");
+    }
+
+    #[test]
+    fn lower_broken() {
+        let cases = vec![
+            ("", "<broken>"),
+            ("λx:. x", "<broken>"),
+            ("f (λ. x)", "f <broken>"),
+            ("let x = in x", "<broken>"),
+            ("λx x", "λx. x"),
+            ("let x unit in x", "let x = unit in x"),
+            ("(λx. x", "λx. x"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(lower_str(input), expected, "input: {}", input);
+        }
+    }
+
+    #[test]
+    fn lower_definitions() {
+        let cases = vec![
+            ("let x = f unit", Some(("x", "f unit"))),
+            ("let x:Unit = unit", Some(("x:Unit", "unit"))),
+            ("let x = ", Some(("x", "<broken>"))),
+            ("let x = e in x", None),
+            ("f unit", None),
+        ];
+        for (input, expected) in cases {
+            let mapper = Mapper::new(input);
+            let result = Parser::new(input, &mapper).run(Parser::repl_input);
+            let definition = lower_definition(&result.syntax)
+                .map(|(binder, bindee)| (binder.to_string(), bindee.to_string()));
+            let expected = expected.map(|(binder, bindee)| (binder.into(), bindee.into()));
+            assert_eq!(definition, expected, "input: {}", input);
+        }
+    }
+}
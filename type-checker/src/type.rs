@@ -2,7 +2,7 @@ use std::{fmt::Display, rc::Rc};
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum Type {
-    Var(&'static str),
+    Var(Rc<str>),
     Arrow(Rc<Type>, Rc<Type>),
     Unit,
 }
@@ -17,8 +17,8 @@ impl Display for Type {
     }
 }
 
-pub fn tvar(name: &'static str) -> Type {
-    Type::Var(name)
+pub fn tvar(name: &str) -> Type {
+    Type::Var(name.into())
 }
 
 pub fn arrow(param: Type, res: Type) -> Type {
@@ -0,0 +1,95 @@
+use std::fmt::Display;
+
+/// A single instruction of the virtual machine. All instructions operate on
+/// the operand stack and the locals of the current frame.
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Instr {
+    /// Push `unit`.
+    UNIT,
+    /// Push the local in the given slot of the current frame.
+    LOCAL(u32),
+    /// Push the captured variable with the given index of the current closure.
+    CAPTURED(u32),
+    /// Push a closure for the code block with the given index. The captured
+    /// variables are taken from the current frame as described by the
+    /// `captures` of the code block.
+    CLOSURE(u32),
+    /// Pop an argument and a closure and call the closure on the argument.
+    APP,
+    /// Return from the current frame, leaving its result on the stack.
+    RET,
+    /// Pop a value and bind it to the next free local slot.
+    LET,
+    /// Release the most recently bound local slot.
+    ENDLET,
+}
+
+/// Where a closure finds the value of a captured variable at the time of its
+/// creation.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Capture {
+    Local(u32),
+    Captured(u32),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Code {
+    /// Name for display purposes, usually the name of the parameter.
+    pub name: String,
+    pub captures: Vec<Capture>,
+    pub instrs: Vec<Instr>,
+}
+
+/// A compiled program. The code block at index `Program::MAIN` is the
+/// entry point and takes no argument.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Program {
+    pub codes: Vec<Code>,
+}
+
+impl Program {
+    pub const MAIN: u32 = 0;
+}
+
+impl Display for Instr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UNIT => write!(f, "UNIT"),
+            Self::LOCAL(slot) => write!(f, "LOCAL {}", slot),
+            Self::CAPTURED(index) => write!(f, "CAPTURED {}", index),
+            Self::CLOSURE(code) => write!(f, "CLOSURE #{}", code),
+            Self::APP => write!(f, "APP"),
+            Self::RET => write!(f, "RET"),
+            Self::LET => write!(f, "LET"),
+            Self::ENDLET => write!(f, "ENDLET"),
+        }
+    }
+}
+
+impl Display for Capture {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Local(slot) => write!(f, "local {}", slot),
+            Self::Captured(index) => write!(f, "captured {}", index),
+        }
+    }
+}
+
+/// The disassembler.
+impl Display for Program {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, code) in self.codes.iter().enumerate() {
+            write!(f, "#{} {}", index, code.name)?;
+            if !code.captures.is_empty() {
+                let captures: Vec<String> = code.captures.iter().map(Capture::to_string).collect();
+                write!(f, " [{}]", captures.join(", "))?;
+            }
+            writeln!(f, ":")?;
+            for (pc, instr) in code.instrs.iter().enumerate() {
+                writeln!(f, "  {:>4}  {}", pc, instr)?;
+            }
+        }
+        Ok(())
+    }
+}
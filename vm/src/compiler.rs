@@ -0,0 +1,123 @@
+use felix_core::{Expr, Name, Prim};
+
+use crate::bytecode::{Capture, Code, Instr, Program};
+
+#[derive(Debug)]
+pub enum CompileError {
+    UnknownEVar(Name),
+}
+
+pub type Result<T> = std::result::Result<T, CompileError>;
+
+/// Compile a closed expression into a program for the virtual machine.
+/// Every lambda becomes a code block of its own whose free variables are
+/// turned into captured variables (closure conversion).
+pub fn compile(expr: &Expr) -> Result<Program> {
+    let mut compiler = Compiler {
+        codes: vec![None],
+        scopes: vec![Scope::default()],
+    };
+    let mut instrs = Vec::new();
+    compiler.expr(expr, &mut instrs)?;
+    instrs.push(Instr::RET);
+    compiler.codes[Program::MAIN as usize] = Some(Code {
+        name: String::from("main"),
+        captures: Vec::new(),
+        instrs,
+    });
+    let codes = compiler
+        .codes
+        .into_iter()
+        .map(|code| code.expect("all code blocks should be finished"))
+        .collect();
+    Ok(Program { codes })
+}
+
+/// The variables visible in the body of a single code block.
+#[derive(Default)]
+struct Scope {
+    /// The identifiers bound in the local slots, the parameter in slot 0.
+    locals: Vec<Name>,
+    /// The identifiers captured from the enclosing scope, in the order of
+    /// their indices, together with where to find them in there.
+    captures: Vec<(Name, Capture)>,
+}
+
+struct Compiler {
+    /// Code blocks are allocated when compilation of a lambda starts and
+    /// filled in when it finishes.
+    codes: Vec<Option<Code>>,
+    scopes: Vec<Scope>,
+}
+
+impl Compiler {
+    fn resolve(&mut self, depth: usize, ident: &Name) -> Result<Capture> {
+        let scope = &self.scopes[depth];
+        if let Some(slot) = scope.locals.iter().rposition(|local| local == ident) {
+            return Ok(Capture::Local(slot as u32));
+        }
+        if let Some(index) = scope
+            .captures
+            .iter()
+            .position(|(captured, _)| captured == ident)
+        {
+            return Ok(Capture::Captured(index as u32));
+        }
+        if depth == 0 {
+            return Err(CompileError::UnknownEVar(ident.clone()));
+        }
+        let source = self.resolve(depth - 1, ident)?;
+        let captures = &mut self.scopes[depth].captures;
+        captures.push((ident.clone(), source));
+        Ok(Capture::Captured((captures.len() - 1) as u32))
+    }
+
+    fn expr(&mut self, expr: &Expr, instrs: &mut Vec<Instr>) -> Result<()> {
+        match expr {
+            Expr::Var(name) => match self.resolve(self.scopes.len() - 1, name)? {
+                Capture::Local(slot) => instrs.push(Instr::LOCAL(slot)),
+                Capture::Captured(index) => instrs.push(Instr::CAPTURED(index)),
+            },
+            Expr::Abs(param, body) => {
+                let index = self.codes.len();
+                self.codes.push(None);
+                self.scopes.push(Scope {
+                    locals: vec![param.clone()],
+                    captures: Vec::new(),
+                });
+                let mut instrs_body = Vec::new();
+                let res = self.expr(body, &mut instrs_body);
+                let scope = self.scopes.pop().unwrap();
+                res?;
+                instrs_body.push(Instr::RET);
+                self.codes[index] = Some(Code {
+                    name: format!("λ{}", param),
+                    captures: scope
+                        .captures
+                        .into_iter()
+                        .map(|(_, source)| source)
+                        .collect(),
+                    instrs: instrs_body,
+                });
+                instrs.push(Instr::CLOSURE(index as u32));
+            }
+            Expr::App(fun, arg) => {
+                self.expr(fun, instrs)?;
+                self.expr(arg, instrs)?;
+                instrs.push(Instr::APP);
+            }
+            Expr::Let(name, bindee, body) => {
+                self.expr(bindee, instrs)?;
+                instrs.push(Instr::LET);
+                self.scopes.last_mut().unwrap().locals.push(name.clone());
+                let res = self.expr(body, instrs);
+                self.scopes.last_mut().unwrap().locals.pop();
+                res?;
+                instrs.push(Instr::ENDLET);
+            }
+            Expr::Prim(Prim::Unit) => instrs.push(Instr::UNIT),
+            Expr::Tick(_, expr) => self.expr(expr, instrs)?,
+        }
+        Ok(())
+    }
+}
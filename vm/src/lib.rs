@@ -0,0 +1,10 @@
+mod bytecode;
+mod compiler;
+mod machine;
+
+pub use bytecode::{Capture, Code, Instr, Program};
+pub use compiler::{compile, CompileError};
+pub use machine::{run, Closure, Machine, RuntimeError, Value};
+
+#[cfg(test)]
+mod tests;
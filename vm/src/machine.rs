@@ -0,0 +1,151 @@
+use std::{fmt::Display, rc::Rc};
+
+use crate::bytecode::{Capture, Instr, Program};
+
+#[derive(Clone, Debug)]
+pub enum Value {
+    Unit,
+    Closure(Rc<Closure>),
+}
+
+#[derive(Debug)]
+pub struct Closure {
+    pub code: u32,
+    pub captured: Vec<Value>,
+}
+
+impl Drop for Closure {
+    fn drop(&mut self) {
+        // NOTE: Long chains of closures capturing closures are common when
+        // running deeply recursive programs. Dropping them recursively would
+        // exhaust the native stack.
+        let mut pending = std::mem::take(&mut self.captured);
+        while let Some(value) = pending.pop() {
+            if let Value::Closure(closure) = value {
+                if let Ok(mut closure) = Rc::try_unwrap(closure) {
+                    pending.append(&mut closure.captured);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RuntimeError {
+    NotAFunction(Value),
+}
+
+pub type Result<T> = std::result::Result<T, RuntimeError>;
+
+struct Frame {
+    closure: Rc<Closure>,
+    pc: usize,
+    /// Index of local slot 0 of this frame in `Machine::locals`.
+    base: usize,
+}
+
+/// Stack machine executing a compiled program. The machine keeps all frames
+/// on the heap and hence runs deeply recursive programs without exhausting
+/// the native stack.
+pub struct Machine<'a> {
+    program: &'a Program,
+    stack: Vec<Value>,
+    locals: Vec<Value>,
+    frames: Vec<Frame>,
+}
+
+impl<'a> Machine<'a> {
+    pub fn new(program: &'a Program) -> Self {
+        Self {
+            program,
+            stack: Vec::new(),
+            locals: Vec::new(),
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn run(mut self) -> Result<Value> {
+        let mut frame = Frame {
+            closure: Rc::new(Closure {
+                code: Program::MAIN,
+                captured: Vec::new(),
+            }),
+            pc: 0,
+            base: 0,
+        };
+        loop {
+            let instr = self.program.codes[frame.closure.code as usize].instrs[frame.pc];
+            frame.pc += 1;
+            match instr {
+                Instr::UNIT => self.stack.push(Value::Unit),
+                Instr::LOCAL(slot) => {
+                    let value = self.locals[frame.base + slot as usize].clone();
+                    self.stack.push(value);
+                }
+                Instr::CAPTURED(index) => {
+                    let value = frame.closure.captured[index as usize].clone();
+                    self.stack.push(value);
+                }
+                Instr::CLOSURE(code) => {
+                    let captured = self.program.codes[code as usize]
+                        .captures
+                        .iter()
+                        .map(|capture| match *capture {
+                            Capture::Local(slot) => self.locals[frame.base + slot as usize].clone(),
+                            Capture::Captured(index) => {
+                                frame.closure.captured[index as usize].clone()
+                            }
+                        })
+                        .collect();
+                    self.stack
+                        .push(Value::Closure(Rc::new(Closure { code, captured })));
+                }
+                Instr::APP => {
+                    let arg = self.stack.pop().expect("operand stack underflow");
+                    let closure = match self.stack.pop().expect("operand stack underflow") {
+                        Value::Closure(closure) => closure,
+                        value => return Err(RuntimeError::NotAFunction(value)),
+                    };
+                    let base = self.locals.len();
+                    self.locals.push(arg);
+                    let caller = std::mem::replace(
+                        &mut frame,
+                        Frame {
+                            closure,
+                            pc: 0,
+                            base,
+                        },
+                    );
+                    self.frames.push(caller);
+                }
+                Instr::RET => {
+                    self.locals.truncate(frame.base);
+                    match self.frames.pop() {
+                        Some(caller) => frame = caller,
+                        None => return Ok(self.stack.pop().expect("operand stack underflow")),
+                    }
+                }
+                Instr::LET => {
+                    let value = self.stack.pop().expect("operand stack underflow");
+                    self.locals.push(value);
+                }
+                Instr::ENDLET => {
+                    self.locals.pop();
+                }
+            }
+        }
+    }
+}
+
+pub fn run(program: &Program) -> Result<Value> {
+    Machine::new(program).run()
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unit => write!(f, "unit"),
+            Self::Closure(closure) => write!(f, "<closure #{}>", closure.code),
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use assert_matches::assert_matches;
+use insta::assert_snapshot;
+
+use felix_core::*;
+
+use super::*;
+
+fn eval(expr: Expr) -> Value {
+    let program = compile(&expr).unwrap();
+    run(&program).unwrap()
+}
+
+fn church_two() -> Expr {
+    abs("f", abs("x", app(var("f"), app(var("f"), var("x")))))
+}
+
+#[test]
+fn run_unit() {
+    assert_matches!(eval(unit()), Value::Unit);
+}
+
+#[test]
+fn run_abs() {
+    assert_matches!(eval(abs("x", var("x"))), Value::Closure(_));
+}
+
+#[test]
+fn run_app() {
+    let expr = app(abs("x", var("x")), unit());
+    assert_matches!(eval(expr), Value::Unit);
+}
+
+#[test]
+fn run_let() {
+    let expr = let_("x", unit(), var("x"));
+    assert_matches!(eval(expr), Value::Unit);
+}
+
+#[test]
+fn run_let_shadowing() {
+    let expr = let_("x", abs("y", var("y")), let_("x", unit(), var("x")));
+    assert_matches!(eval(expr), Value::Unit);
+}
+
+#[test]
+fn run_let_scope_ends() {
+    let expr = app(
+        let_("x", abs("y", var("y")), var("x")),
+        let_("z", unit(), var("z")),
+    );
+    assert_matches!(eval(expr), Value::Unit);
+}
+
+#[test]
+fn run_captured() {
+    // The closure for `λy. x` outlives the frame of `λx`.
+    let expr = app(
+        app(abs("x", abs("y", var("x"))), unit()),
+        abs("z", var("z")),
+    );
+    assert_matches!(eval(expr), Value::Unit);
+}
+
+#[test]
+fn run_captured_transitively() {
+    let expr = let_(
+        "u",
+        unit(),
+        app(
+            app(
+                app(abs("x", abs("y", abs("z", var("u")))), var("u")),
+                var("u"),
+            ),
+            var("u"),
+        ),
+    );
+    assert_matches!(eval(expr), Value::Unit);
+}
+
+#[test]
+fn run_church_exponentiation() {
+    // `two two two two` is the Church numeral for 2^16. Applying it to
+    // `step` and `k` builds a chain of 65536 closures, which are then called
+    // in a nested fashion.
+    let step = abs("k", abs("u", app(var("k"), var("u"))));
+    let expr = let_(
+        "two",
+        church_two(),
+        let_(
+            "n",
+            app(app(app(var("two"), var("two")), var("two")), var("two")),
+            app(app(app(var("n"), step), abs("u", var("u"))), unit()),
+        ),
+    );
+    assert_matches!(eval(expr), Value::Unit);
+}
+
+#[test]
+fn compile_unknown_evar() {
+    assert_matches!(compile(&var("x")), Err(CompileError::UnknownEVar(_)));
+}
+
+#[test]
+fn run_not_a_function() {
+    let program = compile(&app(unit(), unit())).unwrap();
+    assert_matches!(run(&program), Err(RuntimeError::NotAFunction(Value::Unit)));
+}
+
+#[test]
+fn agrees_with_interpreter() {
+    let step = abs("k", abs("u", app(var("k"), var("u"))));
+    let corpus = vec![
+        unit(),
+        abs("x", var("x")),
+        app(abs("x", var("x")), unit()),
+        app(unit(), unit()),
+        app(abs("x", app(var("x"), unit())), abs("y", unit())),
+        app(abs("x", app(var("x"), unit())), unit()),
+        let_(
+            "x",
+            unit(),
+            let_("f", abs("y", var("x")), app(var("f"), var("f"))),
+        ),
+        let_(
+            "two",
+            church_two(),
+            app(
+                app(app(app(var("two"), var("two")), step), abs("u", var("u"))),
+                unit(),
+            ),
+        ),
+    ];
+    for expr in corpus {
+        let expected = felix_interpreter::eval(&expr);
+        let actual = run(&compile(&expr).unwrap());
+        match (&expected, &actual) {
+            (Ok(felix_interpreter::Value::Unit), Ok(Value::Unit))
+            | (Ok(felix_interpreter::Value::Closure(_)), Ok(Value::Closure(_)))
+            | (
+                Err(felix_interpreter::EvalError::NotAFunction(_)),
+                Err(RuntimeError::NotAFunction(_)),
+            ) => {}
+            _ => panic!(
+                "{}: interpreter gives {:?}, vm gives {:?}",
+                expr, expected, actual
+            ),
+        }
+    }
+}
+
+#[test]
+fn disassemble_twice() {
+    let expr = let_(
+        "twice",
+        abs("f", abs("u", app(var("f"), app(var("f"), var("u"))))),
+        app(app(var("twice"), abs("u", var("u"))), unit()),
+    );
+    let program = compile(&expr).unwrap();
+    assert_snapshot!(program, @r"
+    #0 main:
+         0  CLOSURE #1
+         1  LET
+         2  LOCAL 0
+         3  CLOSURE #3
+         4  APP
+         5  UNIT
+         6  APP
+         7  ENDLET
+         8  RET
+    #1 λf:
+         0  CLOSURE #2
+         1  RET
+    #2 λu [local 0]:
+         0  CAPTURED 0
+         1  CAPTURED 0
+         2  LOCAL 0
+         3  APP
+         4  APP
+         5  RET
+    #3 λu:
+         0  LOCAL 0
+         1  RET
+    ");
+}
+
+/// The Church numeral for `n`, written out as `n` nested applications.
+fn church_numeral(n: usize) -> Expr {
+    let body = (0..n).fold(var("x"), |body, _| app(var("f"), body));
+    abs("f", abs("x", body))
+}
+
+/// `n` nested `let`s, each passing the previous value through the
+/// identity.
+fn let_chain(n: usize) -> Expr {
+    let body = (1..=n).rev().fold(var(&format!("x{}", n)), |body, i| {
+        let bindee = app(var("id"), var(&format!("x{}", i - 1)));
+        let_(&format!("x{}", i), bindee, body)
+    });
+    let_("id", abs("y", var("y")), let_("x0", unit(), body))
+}
+
+/// Generated programs whose evaluation recurses `n` levels deep.
+fn deep_recursion_corpus(n: usize) -> Vec<(String, Expr)> {
+    let step = abs("k", abs("u", app(var("k"), var("u"))));
+    vec![
+        (
+            format!("church({})", n),
+            app(
+                app(app(church_numeral(n), step), abs("u", var("u"))),
+                unit(),
+            ),
+        ),
+        (format!("lets({})", n), let_chain(n)),
+    ]
+}
+
+/// The best of several runs of `f`.
+fn time<T>(f: impl Fn() -> T) -> std::time::Duration {
+    (0..5)
+        .map(|_| {
+            let start = std::time::Instant::now();
+            std::hint::black_box(f());
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+/// Compare the VM with the tree-walking interpreter on generated deep
+/// recursions. Run with
+/// `cargo test -p felix-vm --release -- --ignored --nocapture`.
+#[test]
+#[ignore]
+fn bench_deep_recursion() {
+    // NOTE: Both the interpreter and the compiler recurse on the structure
+    // of the term and hence need a big stack.
+    let handle = std::thread::Builder::new()
+        .stack_size(1 << 30)
+        .spawn(|| {
+            for n in [1_000, 10_000, 30_000] {
+                for (name, expr) in deep_recursion_corpus(n) {
+                    let program = compile(&expr).unwrap();
+                    assert_matches!(run(&program), Ok(Value::Unit));
+                    let interpreter = time(|| felix_interpreter::eval(&expr).unwrap());
+                    let vm = time(|| run(&program).unwrap());
+                    println!(
+                        "{:<14} interpreter {:>10.3?}  vm {:>10.3?}  speedup {:.1}x",
+                        name,
+                        interpreter,
+                        vm,
+                        interpreter.as_secs_f64() / vm.as_secs_f64()
+                    );
+                    assert!(vm < interpreter, "{}: vm is slower", name);
+                }
+            }
+        })
+        .unwrap();
+    handle.join().unwrap();
+}
@@ -1,10 +1,13 @@
 use serde::{Deserialize, Serialize};
 use tsify_next::{declare, Tsify};
-use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::prelude::{wasm_bindgen, JsError};
 
-use felix_common::Problem;
+use felix_common::{
+    srcloc::{Encoding, Mapper},
+    Code, Problem, SrcLoc, SrcSpan,
+};
+use felix_ir::{church, ski};
 use felix_parser::Parser;
-use felix_type_checker;
 
 pub mod syntax;
 
@@ -26,23 +29,145 @@ pub struct ParseResult {
 
 #[wasm_bindgen]
 pub fn parse(input: &str, options: ParseOptions) -> ParseResult {
+    Document::new(input.to_string()).parse(options)
+}
+
+/// A document the editor keeps alive and updates with its edits instead of
+/// handing over the whole text again for every change.
+#[wasm_bindgen]
+pub struct Document {
+    mapper: Mapper,
+}
+
+#[wasm_bindgen]
+impl Document {
+    #[wasm_bindgen(constructor)]
+    pub fn new(text: String) -> Self {
+        console_error_panic_hook::set_once();
+        // NOTE: The editor counts columns in UTF-16 code units.
+        Self {
+            mapper: Mapper::new(text).with_encoding(Encoding::Utf16),
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.mapper.text().to_string()
+    }
+
+    /// Replace the text between two positions with `text`. Fails if the
+    /// positions are not within the document.
+    pub fn edit(
+        &mut self,
+        start_line: u32,
+        start_column: u32,
+        end_line: u32,
+        end_column: u32,
+        text: &str,
+    ) -> Result<(), JsError> {
+        let start = self.mapper.index(SrcLoc::new(start_line, start_column));
+        let end = self.mapper.index(SrcLoc::new(end_line, end_column));
+        match (start, end) {
+            (Some(start), Some(end)) if start <= end => {
+                self.mapper.edit(SrcSpan { start, end }, text);
+                Ok(())
+            }
+            _ => Err(JsError::new("edit outside of the document")),
+        }
+    }
+
+    pub fn parse(&self, options: ParseOptions) -> ParseResult {
+        let parser = Parser::new(self.mapper.text(), &self.mapper);
+        let result = parser.run(Parser::program);
+        let syntax = syntax::Element::Node(syntax::Node::from_parser_node(
+            result.syntax,
+            String::from(""),
+            options.include_trivia,
+            &self.mapper,
+        ));
+        ParseResult {
+            problems: result.problems,
+            syntax,
+        }
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Eq, Ord, PartialEq, PartialOrd, Deserialize, Tsify)]
+#[tsify(from_wasm_abi)]
+pub enum Translation {
+    SKI,
+    SKI_BC,
+    CHURCH,
+}
+
+/// Render the program after translating it into combinators or Church
+/// encodings. Fails if the program does not parse.
+#[wasm_bindgen]
+pub fn translate(input: &str, translation: Translation) -> Result<String, JsError> {
     console_error_panic_hook::set_once();
-    let mapper = felix_common::srcloc::Mapper::new(input);
+    render_translation(input, translation).map_err(|error| JsError::new(&error))
+}
+
+// NOTE: `JsError` only exists on wasm32, hence the split from `translate`.
+fn render_translation(input: &str, translation: Translation) -> Result<String, String> {
+    let mapper = Mapper::new(input);
     let parser = Parser::new(input, &mapper);
     let result = parser.run(Parser::program);
-    let syntax = syntax::Element::Node(syntax::Node::from_parser_node(
-        result.syntax,
-        String::from(""),
-        options.include_trivia,
-        &mapper,
-    ));
-    ParseResult {
-        problems: result.problems,
-        syntax,
+    // NOTE: The parser recovers from syntax errors and lowering completes
+    // the AST, so we must not translate a program with problems.
+    if let Some(problem) = result.problems.first() {
+        return Err(format!(
+            "{}:{}: {}",
+            problem.start.line + 1,
+            problem.start.column + 1,
+            problem.message
+        ));
     }
+    let expr = felix_type_checker::lower::lower(&result.syntax);
+    let output = match translation {
+        Translation::SKI => ski::convert(&expr, ski::Basis::SKI).map(|term| term.to_string()),
+        Translation::SKI_BC => ski::convert(&expr, ski::Basis::SKIBC).map(|term| term.to_string()),
+        Translation::CHURCH => church::convert(&expr).map(|expr| expr.to_string()),
+    };
+    output.map_err(|error| error.to_string())
+}
+
+/// The Markdown explanation of a problem code, if the code exists.
+#[wasm_bindgen]
+pub fn explain(code: &str) -> Option<String> {
+    let code: Code = code.parse().ok()?;
+    Some(felix_common::explain::explain(code).to_string())
 }
 
 #[wasm_bindgen]
 pub fn type_system_name() -> String {
     felix_type_checker::stlc::get().name.clone()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translate_ok() {
+        assert_eq!(
+            render_translation("λx. x", Translation::SKI).as_deref(),
+            Ok("I")
+        );
+    }
+
+    #[test]
+    fn translate_syntax_errors() {
+        for (input, error) in [
+            ("λx x", "1:3: Expected `.`, found `x`."),
+            ("let x unit in x", "1:6: Expected `=`, found `unit`."),
+        ] {
+            for translation in [Translation::SKI, Translation::SKI_BC, Translation::CHURCH] {
+                assert_eq!(
+                    render_translation(input, translation),
+                    Err(String::from(error))
+                );
+            }
+        }
+    }
+}
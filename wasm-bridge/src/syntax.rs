@@ -1,7 +1,7 @@
 use serde::Serialize;
 use tsify_next::Tsify;
 
-use felix_common::{srcloc::Mapper, SrcLoc};
+use felix_common::{srcloc::Mapper, SrcLoc, SrcSpan};
 use felix_parser::syntax as parser;
 
 #[derive(Clone, Eq, Ord, PartialEq, PartialOrd, Serialize, Tsify)]
@@ -10,6 +10,7 @@ pub struct Node {
     id: String,
     start: SrcLoc,
     end: SrcLoc,
+    span: SrcSpan<u32>,
     kind: String,
     children: Vec<Element>,
 }
@@ -20,6 +21,7 @@ pub struct Token {
     id: String,
     start: SrcLoc,
     end: SrcLoc,
+    span: SrcSpan<u32>,
     kind: String,
     text: String,
 }
@@ -38,6 +40,7 @@ impl Node {
             id: String::from(""),
             start: SrcLoc { line: 0, column: 0 },
             end: SrcLoc { line: 0, column: 0 },
+            span: SrcSpan::default(),
             kind: String::from("FAKE"),
             children: vec![],
         }
@@ -76,6 +79,10 @@ impl Node {
             id,
             start: mapper.src_loc(span.start().into()),
             end: mapper.src_loc(span.end().into()),
+            span: SrcSpan {
+                start: span.start().into(),
+                end: span.end().into(),
+            },
             kind: format!("{:?}", node.kind()),
             children,
         }
@@ -89,6 +96,10 @@ impl Token {
             id,
             start: mapper.src_loc(span.start().into()),
             end: mapper.src_loc(span.end().into()),
+            span: SrcSpan {
+                start: span.start().into(),
+                end: span.end().into(),
+            },
             kind: format!("{:?}", token.kind()),
             text: token.text().to_string(),
         }
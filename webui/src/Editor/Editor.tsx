@@ -6,7 +6,7 @@ import AceEditor, { IAnnotation, IMarker } from "react-ace";
 import "ace-builds/src-noconflict/ext-language_tools";
 import "ace-builds/src-noconflict/theme-github_light_default";
 
-import type { Problem, SrcLoc } from "felix-wasm-bridge";
+import type { Problem, Severity, SrcLoc } from "felix-wasm-bridge";
 import { useDebouncedCallback } from "@mantine/hooks";
 import { useAppState, useAppStateDispatch } from "../AppState/hooks";
 import { vars } from "../theme";
@@ -49,12 +49,19 @@ function rangeToSpan(range: IAceRange): SrcSpan {
     return { start: pointToLoc(range.start), end: pointToLoc(range.end) };
 }
 
+const ANNOTATION_TYPES: Record<Severity, IAnnotation["type"]> = {
+    ERROR: "error",
+    WARNING: "warning",
+    INFO: "info",
+    HINT: "info",
+};
+
 function makeAnnotation(problem: Problem): IAnnotation {
     return {
         row: problem.start.line,
         column: problem.start.column,
-        text: problem.message,
-        type: problem.severity.toLowerCase() as "error",
+        text: `${problem.message} [${problem.code}]`,
+        type: ANNOTATION_TYPES[problem.severity],
     };
 }
 
@@ -21,6 +21,15 @@ export class LambdaHighlightRules extends window.ace.acequire("ace/mode/text_hig
 
         this.$rules = {
             start: [
+                {
+                    token: "comment.block",
+                    regex: /#\|/,
+                    push: "block_comment",
+                },
+                {
+                    token: "comment.doc",
+                    regex: /##.*$/,
+                },
                 {
                     token: "comment",
                     regex: /#.*$/,
@@ -67,7 +76,24 @@ export class LambdaHighlightRules extends window.ace.acequire("ace/mode/text_hig
                     regex: /\s+/,
                 },
             ],
+            // Block comments nest.
+            block_comment: [
+                {
+                    token: "comment.block",
+                    regex: /#\|/,
+                    push: "block_comment",
+                },
+                {
+                    token: "comment.block",
+                    regex: /\|#/,
+                    next: "pop",
+                },
+                {
+                    defaultToken: "comment.block",
+                },
+            ],
         };
+        this.normalizeRules();
     }
 }
 
@@ -20,8 +20,18 @@ export const locAnn = style({
     fontSize: vars.fontSizes.sm,
 });
 
-export const problemIcon = style({
-    color: vars.colors.red.filled,
+export const note = style({
+    color: vars.colors.placeholder,
+    fontSize: vars.fontSizes.sm,
+});
+
+const problemIcon = style({
     height: rem(22),
     width: rem(22),
 });
+
+export const errorIcon = style([problemIcon, { color: vars.colors.red.filled }]);
+
+export const warningIcon = style([problemIcon, { color: vars.colors.yellow.filled }]);
+
+export const infoIcon = style([problemIcon, { color: vars.colors.blue.filled }]);
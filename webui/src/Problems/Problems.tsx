@@ -1,9 +1,23 @@
 import { MouseEvent, useCallback } from "react";
-import { IconCircleX } from "@tabler/icons-react";
+import { IconAlertTriangle, IconBulb, IconCircleX, IconInfoCircle } from "@tabler/icons-react";
+import type { Severity } from "felix-wasm-bridge";
 import { Box, List, ScrollArea } from "@mantine/core";
 import { useAppState } from "../AppState/hooks";
 import * as classes from "./Problems.css";
 
+function ProblemIcon({ severity }: { severity: Severity }) {
+    switch (severity) {
+        case "ERROR":
+            return <IconCircleX className={classes.errorIcon} />;
+        case "WARNING":
+            return <IconAlertTriangle className={classes.warningIcon} />;
+        case "INFO":
+            return <IconInfoCircle className={classes.infoIcon} />;
+        case "HINT":
+            return <IconBulb className={classes.infoIcon} />;
+    }
+}
+
 export default function ProblemsPane() {
     const { problems, gotoCursor } = useAppState();
     const onProblemClick = useCallback(
@@ -21,19 +35,25 @@ export default function ProblemsPane() {
     return (
         <ScrollArea type="scroll" h="100%">
             <Box pt="xs">
-                <List
-                    className={classes.problemsList}
-                    center
-                    icon={<IconCircleX className={classes.problemIcon} />}
-                >
-                    {problems.map(({ start, message, source }, i) => {
+                <List className={classes.problemsList} center>
+                    {problems.map(({ start, severity, code, message, notes, source }, i) => {
                         const { line, column } = start;
                         return (
-                            <List.Item key={i} data-index={i} onClick={onProblemClick}>
+                            <List.Item
+                                key={i}
+                                data-index={i}
+                                onClick={onProblemClick}
+                                icon={<ProblemIcon severity={severity} />}
+                            >
                                 {message}{" "}
                                 <span className={classes.locAnn}>
-                                    — {source} [Ln {line}, Col {column}]
+                                    — {source} {code} [Ln {line}, Col {column}]
                                 </span>
+                                {notes.map((note, j) => (
+                                    <div key={j} className={classes.note}>
+                                        note: {note}
+                                    </div>
+                                ))}
                             </List.Item>
                         );
                     })}